@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 mod bullet;
 mod engine;
@@ -8,6 +9,7 @@ mod physics;
 mod turret;
 mod worth_points;
 mod rotator;
+mod status;
 
 // Complex components
 pub use bullet::*;
@@ -18,6 +20,7 @@ pub use physics::*;
 pub use turret::*;
 pub use worth_points::*;
 pub use rotator::*;
+pub use status::*;
 
 use crate::util::Colour;
 
@@ -35,6 +38,7 @@ pub struct ShipBundle {
     pub despawn_with_scene: DespawnWithScene,
     pub explodes_on_despawn: ExplodesOnDespawn,
     pub hit_flash: HitFlash,
+    pub status_effects: StatusEffects,
 }
 
 #[derive(Bundle, Default)]
@@ -112,6 +116,14 @@ pub struct ExplosionRender {
     pub fade_out: bool,
 }
 
+/// Pushes everything within an explosion away from its origin, once
+#[derive(Component)]
+pub struct Shockwave {
+    pub force: f32,
+    /// Left in place, the ship that fired the exploding bullet
+    pub ignore: Option<Entity>,
+}
+
 #[derive(Component)]
 pub struct ShouldDespawn;
 
@@ -128,6 +140,8 @@ pub struct ExplodesOnDespawn {
     pub duration_max: f32,
     pub size_min: f32,
     pub size_max: f32,
+    /// Push on everything nearby, shared between the explosions
+    pub force: f32,
 }
 
 impl Default for ExplodesOnDespawn {
@@ -141,6 +155,7 @@ impl Default for ExplodesOnDespawn {
             size_min: 40.0,
             size_max: 40.0,
             spread: 10.0,
+            force: 200.0,
         }
     }
 }
@@ -199,3 +214,42 @@ pub struct Damage {
     pub amount: i32,
     pub is_crit: bool,
 }
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum DamageKind {
+    #[default]
+    Kinetic,
+    Energy,
+    Explosive,
+}
+
+impl std::fmt::Display for DamageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DamageKind::Kinetic => write!(f, "Kinetic"),
+            DamageKind::Energy => write!(f, "Energy"),
+            DamageKind::Explosive => write!(f, "Explosive"),
+        }
+    }
+}
+
+/// Who and what dealt a hit
+#[derive(Copy, Clone, Default)]
+pub struct DamageSource {
+    /// Ship that fired the weapon
+    pub attacker: Option<Entity>,
+    pub class: Option<TurretClass>,
+    pub kind: DamageKind,
+    /// Applied to whatever is hit
+    pub status: Option<StatusEffect>,
+}
+
+impl DamageSource {
+    pub fn weapon(attacker: Entity, class: TurretClass) -> Self {
+        DamageSource { attacker: Some(attacker), class: Some(class), kind: class.damage_kind(), status: None }
+    }
+
+    pub fn with_status(self, status: &AppliesStatus) -> Self {
+        DamageSource { status: status.0, ..self }
+    }
+}
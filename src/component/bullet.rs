@@ -30,6 +30,14 @@ pub struct Seeker(pub Entity);
 #[derive(Component)]
 pub struct DirectDamage(pub Damage);
 
+/// Weapon a bullet came from
+#[derive(Component)]
+pub struct FiredBy(pub TurretClass);
+
+/// Source of the last hit on an entity, credited with the kill
+#[derive(Component)]
+pub struct LastHitBy(pub DamageSource);
+
 #[derive(Component)]
 pub struct AoeDamage {
     pub damage: Damage,
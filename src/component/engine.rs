@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
 #[derive(Component)]
 pub struct Engine {
@@ -27,7 +28,9 @@ impl Engine {
 
 }
 
+#[derive(Clone, Copy, Default, Deserialize)]
 pub enum EngineMethod {
+    #[default]
     Approach,
     Keep(f32),
     #[allow(dead_code)]
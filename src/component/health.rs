@@ -1,4 +1,7 @@
 use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{DamageKind, StatusEffects};
 
 #[derive(Component)]
 pub struct Health {
@@ -8,6 +11,9 @@ pub struct Health {
     pub max_shield: i32,
     pub shield_recharge_cooldown: Timer,
     pub shield_recharge_timer: Timer,
+    /// Fractions of a point left over by scaled hits, dealt once they add up to a whole point
+    pub shield_fraction: f32,
+    pub armour_fraction: f32,
 }
 
 impl Default for Health {
@@ -24,18 +30,34 @@ impl Health {
             max_shield,
             shield_recharge_cooldown: Timer::from_seconds(3.0, TimerMode::Once),
             shield_recharge_timer: Timer::from_seconds(2.0, TimerMode::Repeating),
+            shield_fraction: 0.0,
+            armour_fraction: 0.0,
         }
     }
 
-    pub fn take_damage(&mut self, amount: i32) {
+    /// Resistance scales the damage against the shield and against the armour once the shield is gone,
+    /// vulnerability amplifies both. Returns the damage dealt to each
+    pub fn take_damage(&mut self, amount: i32, kind: DamageKind, resistance: Option<&Resistance>, effects: Option<&StatusEffects>) -> (i32, i32) {
+        let vulnerability = effects.map(|effects| effects.damage_taken_scale()).unwrap_or(1.0);
+        let (shield_scale, armour_scale) = resistance.map(|resistance| resistance.against(kind)).unwrap_or((1.0, 1.0));
+        let (shield_scale, armour_scale) = (shield_scale * vulnerability, armour_scale * vulnerability);
         self.shield_recharge_cooldown.reset();
         self.shield_recharge_timer.reset();
-        if amount > self.shield {
-            self.health -= amount - self.shield;
-            self.shield = 0;
+        let amount = amount.max(0) as f32;
+        let shield = self.shield.max(0) as f32;
+        // Without a shield, or one that takes nothing from this kind, the whole hit goes to the armour
+        let (to_shield, left_over) = if shield_scale <= 0.0 || shield <= 0.0 {
+            (0.0, amount)
+        } else if amount * shield_scale <= shield {
+            (amount * shield_scale, 0.0)
         } else {
-            self.shield -= amount;
-        }
+            (shield, amount - shield / shield_scale)
+        };
+        let to_shield = carry(&mut self.shield_fraction, to_shield);
+        let to_armour = carry(&mut self.armour_fraction, left_over * armour_scale);
+        self.shield -= to_shield;
+        self.health -= to_armour;
+        (to_shield, to_armour)
     }
 
     pub fn heal(&mut self, amount: i32) {
@@ -51,3 +73,137 @@ impl Health {
         }
     }
 }
+
+/// Whole points of damage dealt, keeping the fraction for the next hit so small hits still scale
+fn carry(fraction: &mut f32, damage: f32) -> i32 {
+    let total = damage + *fraction;
+    // Allow for float error so 0.1 * 10 is still a whole point
+    let whole = (total + 1e-4).floor();
+    *fraction = (total - whole).max(0.0);
+    whole as i32
+}
+
+/// Multiplier on each kind of damage, 1.0 takes it in full
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct DamageMultipliers {
+    pub kinetic: f32,
+    pub energy: f32,
+    pub explosive: f32,
+}
+
+impl Default for DamageMultipliers {
+    fn default() -> Self {
+        DamageMultipliers { kinetic: 1.0, energy: 1.0, explosive: 1.0 }
+    }
+}
+
+impl DamageMultipliers {
+    pub fn get(&self, kind: DamageKind) -> f32 {
+        match kind {
+            DamageKind::Kinetic => self.kinetic,
+            DamageKind::Energy => self.energy,
+            DamageKind::Explosive => self.explosive,
+        }
+    }
+}
+
+/// How well the shield and the armour hold up against each kind of damage
+#[derive(Component, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct Resistance {
+    pub shield: DamageMultipliers,
+    pub armour: DamageMultipliers,
+}
+
+impl Resistance {
+    /// Shield and armour multipliers against a kind of damage
+    pub fn against(&self, kind: DamageKind) -> (f32, f32) {
+        (self.shield.get(kind), self.armour.get(kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{DamageSource, StatusEffect, StatusKind};
+
+    fn vulnerable(stacks: u8) -> StatusEffects {
+        let mut effects = StatusEffects::default();
+        for _ in 0..stacks {
+            effects.apply(StatusEffect { kind: StatusKind::Vulnerable, duration: 1.0, potency: 0.5 }, DamageSource::default());
+        }
+        effects
+    }
+
+    #[test]
+    fn shield_takes_damage_before_armour() {
+        let mut health = Health::new(100, 10);
+        assert_eq!(health.take_damage(15, DamageKind::Kinetic, None, None), (10, 5));
+        assert_eq!((health.shield, health.health), (0, 95));
+    }
+
+    #[test]
+    fn resistance_scales_shield_and_armour_separately() {
+        let resistance = Resistance {
+            shield: DamageMultipliers { energy: 0.5, ..Default::default() },
+            armour: DamageMultipliers { energy: 2.0, ..Default::default() },
+        };
+        let mut health = Health::new(100, 10);
+        // 20 energy is 10 to the shield, which absorbs all of it
+        assert_eq!(health.take_damage(20, DamageKind::Energy, Some(&resistance), None), (10, 0));
+        // Once the shield is gone armour takes double
+        assert_eq!(health.take_damage(5, DamageKind::Energy, Some(&resistance), None), (0, 10));
+        // Other kinds are taken in full
+        assert_eq!(health.take_damage(5, DamageKind::Kinetic, Some(&resistance), None), (0, 5));
+    }
+
+    #[test]
+    fn vulnerability_amplifies_damage() {
+        let mut health = Health::new(100, 0);
+        assert_eq!(health.take_damage(10, DamageKind::Kinetic, None, Some(&vulnerable(1))), (0, 15));
+        assert_eq!(health.take_damage(10, DamageKind::Kinetic, None, Some(&vulnerable(2))), (0, 20));
+        assert_eq!(health.take_damage(10, DamageKind::Kinetic, None, Some(&StatusEffects::default())), (0, 10));
+    }
+
+    #[test]
+    fn vulnerability_stacks_with_resistance() {
+        let resistance = Resistance {
+            armour: DamageMultipliers { kinetic: 0.5, ..Default::default() },
+            ..Default::default()
+        };
+        let mut health = Health::new(100, 0);
+        assert_eq!(health.take_damage(10, DamageKind::Kinetic, Some(&resistance), Some(&vulnerable(2))), (0, 10));
+    }
+
+    #[test]
+    fn small_hits_carry_their_fraction() {
+        let resistance = Resistance {
+            shield: DamageMultipliers { kinetic: 0.5, ..Default::default() },
+            ..Default::default()
+        };
+        let mut health = Health::new(100, 10);
+        // Half a point per hit, so every second hit lands
+        assert_eq!(health.take_damage(1, DamageKind::Kinetic, Some(&resistance), None), (0, 0));
+        assert_eq!(health.take_damage(1, DamageKind::Kinetic, Some(&resistance), None), (1, 0));
+        assert_eq!(health.shield, 9);
+
+        // Half a point extra per hit, so four hits deal six
+        let mut health = Health::new(100, 0);
+        let dealt: i32 = (0..4).map(|_| health.take_damage(1, DamageKind::Kinetic, None, Some(&vulnerable(1))).1).sum();
+        assert_eq!(dealt, 6);
+    }
+
+    #[test]
+    fn shield_immune_to_a_kind_passes_it_to_armour() {
+        let resistance = Resistance {
+            shield: DamageMultipliers { energy: 0.0, ..Default::default() },
+            ..Default::default()
+        };
+        let mut health = Health::new(100, 10);
+        assert_eq!(health.take_damage(5, DamageKind::Energy, Some(&resistance), None), (0, 5));
+        health.shield = 0;
+        assert_eq!(health.take_damage(5, DamageKind::Energy, Some(&resistance), None), (0, 5));
+        assert_eq!((health.shield, health.health), (0, 90));
+    }
+}
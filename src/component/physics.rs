@@ -1,11 +1,17 @@
 use bevy::prelude::*;
 
+/// Lightest mass used for impulses
+const MIN_MASS: f32 = 0.01;
+
 #[derive(Component)]
+#[require(TranslationInterpolation)]
 pub struct Physics {
     pub acceleration: Vec2,
     pub velocity: Vec2,
     pub drag: f32,
-    pub face_velocity: bool
+    pub face_velocity: bool,
+    /// Heavier bodies are pushed less by impulses
+    pub mass: f32,
 }
 
 impl Physics {
@@ -17,10 +23,43 @@ impl Physics {
     pub fn add_force(&mut self, force: Vec2) -> () {
         self.acceleration += force;
     }
+
+    /// Sudden push such as a hit or a blast, divided by mass. A zero or negative mass
+    /// from a bad config is treated as very light rather than producing infinite speed
+    pub fn add_impulse(&mut self, impulse: Vec2) {
+        self.add_force(impulse / self.mass.max(MIN_MASS));
+    }
 }
 
 impl Default for Physics {
     fn default() -> Self {
-        Self { acceleration: Vec2::ZERO, velocity: Vec2::ZERO, drag: 0.0, face_velocity: true }
+        Self { acceleration: Vec2::ZERO, velocity: Vec2::ZERO, drag: 0.0, face_velocity: true, mass: 1.0 }
     }
-}
\ No newline at end of file
+}
+/// Simulated translations of the last two fixed steps, rendering blends between them
+#[derive(Component, Default)]
+pub struct TranslationInterpolation {
+    pub previous: Option<Vec3>,
+    pub current: Option<Vec3>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impulse_is_divided_by_mass() {
+        let mut physics = Physics { mass: 4.0, ..Default::default() };
+        physics.add_impulse(Vec2::new(8.0, 0.0));
+        assert_eq!(physics.acceleration, Vec2::new(2.0, 0.0));
+    }
+
+    #[test]
+    fn impulse_stays_finite_without_mass() {
+        for mass in [0.0, -1.0, f32::NAN] {
+            let mut physics = Physics { mass, ..Default::default() };
+            physics.add_impulse(Vec2::new(1.0, 1.0));
+            assert!(physics.acceleration.is_finite(), "mass {}", mass);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::util::Colour;
+
+use super::DamageSource;
+
+/// Seconds between burn damage ticks
+pub const BURN_TICK: f32 = 0.5;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum StatusKind {
+    /// Lowers top speed
+    Slow,
+    /// Damage over time
+    Burn,
+    /// No engine and no turrets firing
+    Stun,
+    /// Takes more damage from every hit
+    Vulnerable,
+}
+
+impl StatusKind {
+    pub const ALL: [StatusKind; 4] = [StatusKind::Slow, StatusKind::Burn, StatusKind::Stun, StatusKind::Vulnerable];
+
+    pub fn max_stacks(&self) -> u8 {
+        match self {
+            StatusKind::Slow => 3,
+            StatusKind::Burn => 5,
+            StatusKind::Stun => 1,
+            StatusKind::Vulnerable => 3,
+        }
+    }
+
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            StatusKind::Slow => "~",
+            StatusKind::Burn => "^",
+            StatusKind::Stun => "z",
+            StatusKind::Vulnerable => "x",
+        }
+    }
+
+    pub fn colour(&self) -> Color {
+        match self {
+            StatusKind::Slow => Colour::SHIELD,
+            StatusKind::Burn => Colour::RED,
+            StatusKind::Stun => Colour::YELLOW,
+            StatusKind::Vulnerable => Colour::PURPLE,
+        }
+    }
+}
+
+/// Effect a hit applies, set in `turrets.ron` and `enemies.ron`
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// Seconds it lasts, refreshed by each new hit
+    pub duration: f32,
+    /// Strength of each stack, the fraction of speed lost for slow, damage per tick for burn
+    /// and extra fraction of damage taken for vulnerable, unused by stun
+    #[serde(default)]
+    pub potency: f32,
+}
+
+/// Status effect a turret passes on to everything it hits
+#[derive(Component, Copy, Clone, Default)]
+pub struct AppliesStatus(pub Option<StatusEffect>);
+
+pub struct ActiveStatus {
+    pub kind: StatusKind,
+    pub stacks: u8,
+    pub potency: f32,
+    pub remaining: Timer,
+    /// Next burn tick
+    pub tick: Timer,
+    /// Credited with burn damage
+    pub source: DamageSource,
+}
+
+/// Status effects currently on a ship
+#[derive(Component, Default)]
+pub struct StatusEffects {
+    pub active: Vec<ActiveStatus>,
+}
+
+impl StatusEffects {
+    /// Add a stack up to the limit and refresh the duration, the strongest potency is kept
+    pub fn apply(&mut self, effect: StatusEffect, source: DamageSource) {
+        let duration = Duration::from_secs_f32(effect.duration.max(0.0));
+        match self.active.iter_mut().find(|active| active.kind == effect.kind) {
+            Some(active) => {
+                active.stacks = (active.stacks + 1).min(effect.kind.max_stacks());
+                active.potency = active.potency.max(effect.potency);
+                if active.remaining.remaining() < duration {
+                    active.remaining = Timer::new(duration, TimerMode::Once);
+                }
+                active.source = source;
+            }
+            None => self.active.push(ActiveStatus {
+                kind: effect.kind,
+                stacks: 1,
+                potency: effect.potency,
+                remaining: Timer::new(duration, TimerMode::Once),
+                tick: Timer::from_seconds(BURN_TICK, TimerMode::Repeating),
+                source,
+            }),
+        }
+    }
+
+    fn strength(&self, kind: StatusKind) -> f32 {
+        self.active
+            .iter()
+            .find(|active| active.kind == kind)
+            .map(|active| active.potency * active.stacks as f32)
+            .unwrap_or(0.0)
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.active.iter().any(|active| active.kind == kind)
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.has(StatusKind::Stun)
+    }
+
+    /// Multiplier on top speed
+    pub fn speed_scale(&self) -> f32 {
+        (1.0 - self.strength(StatusKind::Slow)).clamp(0.1, 1.0)
+    }
+
+    /// Multiplier on damage taken
+    pub fn damage_taken_scale(&self) -> f32 {
+        1.0 + self.strength(StatusKind::Vulnerable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effect(kind: StatusKind, duration: f32, potency: f32) -> StatusEffect {
+        StatusEffect { kind, duration, potency }
+    }
+
+    #[test]
+    fn stacks_up_to_the_limit() {
+        let mut effects = StatusEffects::default();
+        for _ in 0..10 {
+            effects.apply(effect(StatusKind::Burn, 1.0, 1.0), DamageSource::default());
+            effects.apply(effect(StatusKind::Stun, 1.0, 0.0), DamageSource::default());
+        }
+        let stacks = |kind| effects.active.iter().find(|active| active.kind == kind).map(|active| active.stacks);
+        assert_eq!(stacks(StatusKind::Burn), Some(StatusKind::Burn.max_stacks()));
+        assert_eq!(stacks(StatusKind::Stun), Some(1));
+        assert_eq!(effects.active.len(), 2);
+    }
+
+    #[test]
+    fn reapplying_refreshes_to_the_longer_duration() {
+        let mut effects = StatusEffects::default();
+        effects.apply(effect(StatusKind::Slow, 1.0, 0.1), DamageSource::default());
+        effects.active[0].remaining.tick(Duration::from_secs_f32(0.8));
+        effects.apply(effect(StatusKind::Slow, 1.0, 0.1), DamageSource::default());
+        assert_eq!(effects.active[0].remaining.elapsed(), Duration::ZERO);
+
+        // A shorter hit doesn't cut the remaining time
+        effects.apply(effect(StatusKind::Slow, 0.2, 0.1), DamageSource::default());
+        assert_eq!(effects.active[0].remaining.duration(), Duration::from_secs_f32(1.0));
+    }
+
+    #[test]
+    fn strongest_potency_is_kept() {
+        let mut effects = StatusEffects::default();
+        effects.apply(effect(StatusKind::Slow, 1.0, 0.3), DamageSource::default());
+        effects.apply(effect(StatusKind::Slow, 1.0, 0.1), DamageSource::default());
+        // Two stacks at 0.3
+        assert!((effects.speed_scale() - 0.4).abs() < 1e-5);
+    }
+
+    #[test]
+    fn slow_never_stops_a_ship() {
+        let mut effects = StatusEffects::default();
+        for _ in 0..3 {
+            effects.apply(effect(StatusKind::Slow, 1.0, 0.9), DamageSource::default());
+        }
+        assert_eq!(effects.speed_scale(), 0.1);
+        assert!(!effects.is_stunned());
+        assert_eq!(effects.damage_taken_scale(), 1.0);
+    }
+}
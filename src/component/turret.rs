@@ -4,10 +4,11 @@ use rand::{
     distributions::{Distribution, Standard},
     Rng,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::util::Colour;
 
-use super::Damage;
+use super::{AppliesStatus, Damage, DamageKind};
 
 #[derive(Component)]
 pub struct Range {
@@ -42,7 +43,7 @@ pub struct Targets {
     pub target: Option<Entity>,
 }
 
-#[derive(Component, Copy, Clone, Eq, Hash, PartialEq, Default)]
+#[derive(Component, Copy, Clone, Eq, Hash, PartialEq, Default, Serialize, Deserialize)]
 pub enum TurretClass {
     #[default]
     AutoCannon,
@@ -53,6 +54,20 @@ pub enum TurretClass {
     ChainLaser,
     PierceLaser,
     Emp,
+    /// Evolved from the auto cannon
+    VulcanCannon,
+    /// Evolved from the blast laser
+    StormLaser,
+}
+
+impl TurretClass {
+    pub fn damage_kind(&self) -> DamageKind {
+        match self {
+            TurretClass::AutoCannon | TurretClass::ShrapnelCannon | TurretClass::VulcanCannon => DamageKind::Kinetic,
+            TurretClass::BlastLaser | TurretClass::ChainLaser | TurretClass::PierceLaser | TurretClass::Emp | TurretClass::StormLaser => DamageKind::Energy,
+            TurretClass::RocketLauncher | TurretClass::MineLauncher => DamageKind::Explosive,
+        }
+    }
 }
 
 impl Display for TurretClass {
@@ -66,6 +81,8 @@ impl Display for TurretClass {
             TurretClass::ChainLaser =>  write!(f, "Chain Laser"),
             TurretClass::PierceLaser =>  write!(f, "Pierce Laser"),
             TurretClass::Emp => write!(f, "EM Pulsar"),
+            TurretClass::VulcanCannon => write!(f, "Vulcan Cannon"),
+            TurretClass::StormLaser => write!(f, "Storm Laser"),
         }
     }
 }
@@ -96,8 +113,8 @@ impl DoesDamage {
         Self { amount, ..Default::default() }
     }
 
-    pub fn roll(&self) -> Damage {
-        if rand::thread_rng().gen_range(0.0..1.0) < self.crit_chance {
+    pub fn roll(&self, rng: &mut impl Rng) -> Damage {
+        if rng.gen_range(0.0..1.0) < self.crit_chance {
             return Damage { amount: self.amount * 2, is_crit: true };
         }
         Damage { amount: self.amount, is_crit: false }
@@ -123,6 +140,14 @@ impl Default for EffectColour {
     fn default() -> Self { EffectColour(Colour::RED) }
 }
 
+/// Times the weapon has been picked, starting at 1
+#[derive(Component)]
+pub struct TurretLevel(pub u8);
+
+impl Default for TurretLevel {
+    fn default() -> Self { TurretLevel(1) }
+}
+
 #[derive(Bundle, Default)]
 pub struct TurretBundle {
     pub range: Range,
@@ -133,107 +158,6 @@ pub struct TurretBundle {
     pub shots: MultiShot,
     pub size: EffectSize,
     pub colour: EffectColour,
+    pub level: TurretLevel,
+    pub status: AppliesStatus,
 }
-
-impl TurretBundle {
-
-    pub fn auto_cannon() -> TurretBundle {
-        TurretBundle {
-            class: TurretClass::AutoCannon,
-            fire_rate: FireRate::from_rate_in_seconds(2.0),
-            damage: DoesDamage::from_amount(2),
-            colour: EffectColour(Colour::PLAYER),
-            ..Default::default()
-        }
-    }
-
-    pub fn blast_laser() -> TurretBundle {
-        TurretBundle {
-            class: TurretClass::BlastLaser,
-            fire_rate: FireRate::from_rate_in_seconds(1.5),
-            damage: DoesDamage::from_amount(1),
-            colour: EffectColour(Colour::PINK),
-            ..Default::default()
-        }
-    }
-
-    pub fn rocket_launcher() -> TurretBundle {
-        TurretBundle {
-            class: TurretClass::RocketLauncher,
-            fire_rate: FireRate::from_rate_in_seconds(0.5),
-            damage: DoesDamage::from_amount(5),
-            colour: EffectColour(Colour::YELLOW),
-            ..Default::default()
-        }
-    }
-
-    pub fn mine_launcher() -> TurretBundle {
-        TurretBundle {
-            class: TurretClass::MineLauncher,
-            fire_rate: FireRate::from_rate_in_seconds(0.9),
-            damage: DoesDamage::from_amount(6),
-            size: EffectSize(40.0),
-            shots: MultiShot { amount: 3 },
-            colour: EffectColour(Colour::PLAYER),
-            ..Default::default()
-        }
-    }
-
-    pub fn shrapnel_cannon() -> TurretBundle {
-        TurretBundle {
-            class: TurretClass::ShrapnelCannon,
-            fire_rate: FireRate::from_rate_in_seconds(0.25),
-            damage: DoesDamage::from_amount(2),
-            shots: MultiShot { amount: 16 },
-            colour: EffectColour(Colour::PLAYER),
-            ..Default::default()
-        }
-    }
-
-    pub fn chain_laser() -> TurretBundle {
-        TurretBundle {
-            class: TurretClass::ChainLaser,
-            fire_rate: FireRate::from_rate_in_seconds(0.4),
-            damage: DoesDamage::from_amount(1),
-            shots: MultiShot { amount: 3 },
-            colour: EffectColour(Colour::GREEN),
-            ..Default::default()
-        }
-    }
-
-    pub fn pierce_laser() -> TurretBundle {
-        TurretBundle {
-            class: TurretClass::PierceLaser,
-            fire_rate: FireRate::from_rate_in_seconds(0.15),
-            damage: DoesDamage::from_amount(8),
-            size: EffectSize(1.0),
-            colour: EffectColour(Colour::YELLOW),
-            ..Default::default()
-        }
-    }
-
-    pub fn emp() -> TurretBundle {
-        TurretBundle {
-            class: TurretClass::Emp,
-            fire_rate: FireRate::from_rate_in_seconds(0.7),
-            damage: DoesDamage::from_amount(3),
-            size: EffectSize(80.0),
-            colour: EffectColour(Colour::SHIELD),
-            ..Default::default()
-        }
-    }
-
-    pub fn from_class(class: &TurretClass) -> TurretBundle {
-        match class {
-            TurretClass::AutoCannon => TurretBundle::auto_cannon(),
-            TurretClass::BlastLaser => TurretBundle::blast_laser(),
-            TurretClass::RocketLauncher => TurretBundle::rocket_launcher(),
-            TurretClass::MineLauncher => TurretBundle::mine_launcher(),
-            TurretClass::ShrapnelCannon => TurretBundle::shrapnel_cannon(),
-            TurretClass::ChainLaser => TurretBundle::chain_laser(),
-            TurretClass::PierceLaser => TurretBundle::pierce_laser(),
-            TurretClass::Emp => TurretBundle::emp(),
-        }
-    }
-
-}
\ No newline at end of file
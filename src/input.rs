@@ -1,17 +1,264 @@
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect)]
+use crate::util::Storage;
+
+const CONTROLS_FILE: &str = "controls.ron";
+
+/// Bindings an action can have, each edited on its own on the controls screen
+pub const BINDING_SLOTS: usize = 3;
+
+#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect, Serialize, Deserialize)]
 pub enum PlayerAction {
+    /// Fly toward the cursor
     Move,
-    Pause
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    /// Analogue direction relative to the ship
+    #[actionlike(DualAxis)]
+    Steer,
+    Pause,
+    ZoomIn,
+    ZoomOut,
+    SelectFirst,
+    SelectSecond,
+    SelectThird,
 }
 
 impl PlayerAction {
-    /// Define the default bindings to the input
-    pub fn default_input_map() -> InputMap<Self> {
+    /// Actions bound to buttons, which the controls screen can change
+    pub const REBINDABLE: [PlayerAction; 11] = [
+        PlayerAction::Move,
+        PlayerAction::MoveUp,
+        PlayerAction::MoveDown,
+        PlayerAction::MoveLeft,
+        PlayerAction::MoveRight,
+        PlayerAction::Pause,
+        PlayerAction::ZoomIn,
+        PlayerAction::ZoomOut,
+        PlayerAction::SelectFirst,
+        PlayerAction::SelectSecond,
+        PlayerAction::SelectThird,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlayerAction::Move => "Fly To Cursor",
+            PlayerAction::MoveUp => "Move Up",
+            PlayerAction::MoveDown => "Move Down",
+            PlayerAction::MoveLeft => "Move Left",
+            PlayerAction::MoveRight => "Move Right",
+            PlayerAction::Steer => "Steer",
+            PlayerAction::Pause => "Pause",
+            PlayerAction::ZoomIn => "Zoom In",
+            PlayerAction::ZoomOut => "Zoom Out",
+            PlayerAction::SelectFirst => "Pick Upgrade 1",
+            PlayerAction::SelectSecond => "Pick Upgrade 2",
+            PlayerAction::SelectThird => "Pick Upgrade 3",
+        }
+    }
+
+    pub fn default_bindings(&self) -> Vec<Binding> {
+        match self {
+            PlayerAction::Move => vec![Binding::Mouse(MouseButton::Left)],
+            PlayerAction::MoveUp => vec![Binding::Key(KeyCode::KeyW), Binding::Key(KeyCode::ArrowUp), Binding::Gamepad(GamepadButton::DPadUp)],
+            PlayerAction::MoveDown => vec![Binding::Key(KeyCode::KeyS), Binding::Key(KeyCode::ArrowDown), Binding::Gamepad(GamepadButton::DPadDown)],
+            PlayerAction::MoveLeft => vec![Binding::Key(KeyCode::KeyA), Binding::Key(KeyCode::ArrowLeft), Binding::Gamepad(GamepadButton::DPadLeft)],
+            PlayerAction::MoveRight => vec![Binding::Key(KeyCode::KeyD), Binding::Key(KeyCode::ArrowRight), Binding::Gamepad(GamepadButton::DPadRight)],
+            // Always the left stick
+            PlayerAction::Steer => vec![],
+            PlayerAction::Pause => vec![Binding::Key(KeyCode::Escape), Binding::Gamepad(GamepadButton::Start)],
+            PlayerAction::ZoomIn => vec![Binding::Key(KeyCode::NumpadAdd), Binding::Gamepad(GamepadButton::RightTrigger)],
+            PlayerAction::ZoomOut => vec![Binding::Key(KeyCode::NumpadSubtract), Binding::Gamepad(GamepadButton::LeftTrigger)],
+            PlayerAction::SelectFirst => vec![Binding::Key(KeyCode::Digit1), Binding::Gamepad(GamepadButton::West)],
+            PlayerAction::SelectSecond => vec![Binding::Key(KeyCode::Digit2), Binding::Gamepad(GamepadButton::North)],
+            PlayerAction::SelectThird => vec![Binding::Key(KeyCode::Digit3), Binding::Gamepad(GamepadButton::East)],
+        }
+    }
+
+    /// Direction from the movement keys and stick, zero when none are held
+    pub fn direction(action_state: &ActionState<Self>) -> Vec2 {
+        let mut direction = action_state.axis_pair(&Self::Steer);
+        if action_state.pressed(&Self::MoveUp) {
+            direction.y += 1.0;
+        }
+        if action_state.pressed(&Self::MoveDown) {
+            direction.y -= 1.0;
+        }
+        if action_state.pressed(&Self::MoveLeft) {
+            direction.x -= 1.0;
+        }
+        if action_state.pressed(&Self::MoveRight) {
+            direction.x += 1.0;
+        }
+        direction.clamp_length_max(1.0)
+    }
+}
+
+/// Button, key or mouse button an action can be bound to
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+impl Binding {
+    pub fn is_gamepad(&self) -> bool {
+        matches!(self, Binding::Gamepad(_))
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{:?}", key);
+                ["Key", "Digit", "Arrow"]
+                    .iter()
+                    .find_map(|prefix| name.strip_prefix(prefix))
+                    .map(|short| short.to_string())
+                    .unwrap_or(name)
+            }
+            Binding::Mouse(button) => format!("Mouse {:?}", button),
+            Binding::Gamepad(button) => format!("Pad {:?}", button),
+        }
+    }
+
+    fn insert(&self, input_map: &mut InputMap<PlayerAction>, action: PlayerAction) {
+        match *self {
+            Binding::Key(key) => input_map.insert(action, key),
+            Binding::Mouse(button) => input_map.insert(action, button),
+            Binding::Gamepad(button) => input_map.insert(action, button),
+        };
+    }
+}
+
+/// Player chosen bindings, kept between sessions
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Controls {
+    pub bindings: Vec<(PlayerAction, Vec<Binding>)>,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Controls {
+            bindings: PlayerAction::REBINDABLE.iter().map(|action| (*action, action.default_bindings())).collect(),
+        }
+    }
+}
+
+impl Controls {
+    pub fn load() -> Controls {
+        if !Storage::exists(CONTROLS_FILE) {
+            return Controls::default();
+        }
+        match Storage::read(CONTROLS_FILE).and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string())) {
+            Ok(controls) => controls,
+            Err(err) => {
+                warn!("Could not load controls from {}: {}", CONTROLS_FILE, err);
+                Controls::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|err| err.to_string())?;
+        Storage::write(CONTROLS_FILE, &contents)
+    }
+
+    /// Bindings of an action, actions missing from an older file keep their defaults
+    pub fn bindings(&self, action: PlayerAction) -> Vec<Binding> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, bindings)| bindings.clone())
+            .unwrap_or_else(|| action.default_bindings())
+    }
+
+    /// Other action already using the binding
+    pub fn conflict(&self, action: PlayerAction, binding: Binding) -> Option<PlayerAction> {
+        PlayerAction::REBINDABLE
+            .into_iter()
+            .find(|other| *other != action && self.bindings(*other).contains(&binding))
+    }
+
+    /// Set one binding of an action, a slot past the last binding adds one
+    pub fn bind(&mut self, action: PlayerAction, slot: usize, binding: Binding) {
+        let mut bindings = self.bindings(action);
+        match bindings.get_mut(slot) {
+            Some(existing) => *existing = binding,
+            None => bindings.push(binding),
+        }
+        self.set(action, bindings);
+    }
+
+    /// Remove one binding of an action
+    pub fn unbind(&mut self, action: PlayerAction, slot: usize) {
+        let mut bindings = self.bindings(action);
+        if slot < bindings.len() {
+            bindings.remove(slot);
+        }
+        self.set(action, bindings);
+    }
+
+    fn set(&mut self, action: PlayerAction, bindings: Vec<Binding>) {
+        match self.bindings.iter_mut().find(|(bound, _)| *bound == action) {
+            Some((_, existing)) => *existing = bindings,
+            None => self.bindings.push((action, bindings)),
+        }
+    }
+
+    pub fn input_map(&self) -> InputMap<PlayerAction> {
         let mut input_map = InputMap::default();
-        input_map.insert(Self::Move, MouseButton::Left);
+        for action in PlayerAction::REBINDABLE {
+            for binding in self.bindings(action) {
+                binding.insert(&mut input_map, action);
+            }
+        }
+        input_map.insert_dual_axis(PlayerAction::Steer, GamepadStick::LEFT.with_circle_deadzone(0.1));
         input_map
     }
-}
\ No newline at end of file
+}
+
+/// Keep the input map in step with the chosen controls.
+/// The map is a global resource rather than a component on the player so the menus,
+/// level up screen and controls screen can read actions while there is no ship.
+pub fn apply_controls(mut commands: Commands, controls: Res<Controls>) {
+    commands.insert_resource(controls.input_map());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bind_replaces_only_the_edited_slot() {
+        let mut controls = Controls::default();
+        controls.bind(PlayerAction::MoveUp, 0, Binding::Key(KeyCode::KeyI));
+        assert_eq!(
+            controls.bindings(PlayerAction::MoveUp),
+            vec![Binding::Key(KeyCode::KeyI), Binding::Key(KeyCode::ArrowUp), Binding::Gamepad(GamepadButton::DPadUp)]
+        );
+    }
+
+    #[test]
+    fn bind_past_the_end_adds_a_binding() {
+        let mut controls = Controls::default();
+        controls.bind(PlayerAction::Pause, 2, Binding::Key(KeyCode::KeyP));
+        assert_eq!(
+            controls.bindings(PlayerAction::Pause),
+            vec![Binding::Key(KeyCode::Escape), Binding::Gamepad(GamepadButton::Start), Binding::Key(KeyCode::KeyP)]
+        );
+    }
+
+    #[test]
+    fn unbind_removes_one_slot() {
+        let mut controls = Controls::default();
+        controls.unbind(PlayerAction::MoveUp, 1);
+        assert_eq!(controls.bindings(PlayerAction::MoveUp), vec![Binding::Key(KeyCode::KeyW), Binding::Gamepad(GamepadButton::DPadUp)]);
+        controls.unbind(PlayerAction::MoveUp, 5);
+        assert_eq!(controls.bindings(PlayerAction::MoveUp).len(), 2);
+    }
+}
@@ -0,0 +1,398 @@
+pub mod util;
+pub mod component;
+pub mod plugin;
+pub mod resource;
+pub mod system;
+pub mod input;
+
+use bevy::app::{RunFixedMainLoop, RunFixedMainLoopSystem};
+use bevy::ecs::schedule::ExecutorKind;
+use bevy::prelude::*;
+use bevy_embedded_assets::EmbeddedAssetPlugin;
+use bevy_parallax::CreateParallaxEvent;
+use bevy_parallax::{LayerData, LayerSpeed, ParallaxCameraComponent, ParallaxPlugin, ParallaxSystems};
+use bevy_prototype_lyon::prelude::*;
+use component::*;
+use input::{apply_controls, Controls, PlayerAction};
+use leafwing_input_manager::{plugin::InputManagerPlugin, prelude::ActionState};
+use plugin::EnemyPlugin;
+use plugin::HudPlugin;
+use plugin::TurretPlugin;
+use plugin::UpgradePlugin;
+use plugin::MainMenuPlugin;
+use plugin::HighScorePlugin;
+use plugin::{SettingsPlugin, SettingsState, BLOOM_INTENSITY};
+use plugin::SoundPlugin;
+use plugin::MusicPlugin;
+use plugin::SelectionPlugin;
+use plugin::ObjectPlugin;
+use plugin::HeadlessPlugin;
+use plugin::{Playback, ReplayPlugin};
+use plugin::{ResumeRun, SavePlugin};
+use plugin::{BanishedUpgrades, PlayerUpgrades, SelectionCharges, StartingLoadout, UpgradeEvent};
+use util::{RenderLayer, SpatialIndex};
+use util::Colour;
+use resource::*;
+use std::f32::consts::PI;
+use std::path::PathBuf;
+use system::*;
+use bevy::core_pipeline::bloom::{Bloom, BloomCompositeMode};
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum AppState {
+    #[default]
+    Setup,
+    Menu,
+    InGame,
+    /// Passed through to start a new run straight after leaving one
+    Restarting,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum GameState {
+    #[default]
+    Running,
+    Selection,
+    Paused,
+    GameOver,
+}
+
+/// Rate the simulation is stepped at, independent of the frame rate
+pub const SIMULATION_HZ: f64 = 60.0;
+
+/// Stages of a simulation step in the order they run in `FixedUpdate`
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameplaySet {
+    Input,
+    Ai,
+    Engine,
+    Physics,
+    Collision,
+    Damage,
+    Death,
+    Loot,
+}
+
+/// Which parts of the game to build, tools can leave out what they don't need
+#[derive(Clone)]
+pub struct Subsystems {
+    pub enemies: bool,
+    pub space_objects: bool,
+    pub hud: bool,
+    pub replay: bool,
+}
+
+impl Default for Subsystems {
+    fn default() -> Self {
+        Self { enemies: true, space_objects: true, hud: true, replay: true }
+    }
+}
+
+/// The whole game, add to an empty `App` to run it
+pub struct OuterShellPlugin {
+    pub title: String,
+    /// Upgrades given at the start of every run, the starting weapon pick is skipped when set
+    pub starting_loadout: Vec<UpgradeEvent>,
+    pub subsystems: Subsystems,
+    /// Run the simulation without a window, renderer or input
+    pub headless: bool,
+    /// Start every run from this seed instead of a random one
+    pub seed: Option<u64>,
+    /// Replay file to play back instead of live input
+    pub replay: Option<PathBuf>,
+}
+
+impl Default for OuterShellPlugin {
+    fn default() -> Self {
+        Self {
+            title: String::from("Outer Shell"),
+            starting_loadout: vec![],
+            subsystems: Subsystems::default(),
+            headless: false,
+            seed: None,
+            replay: None,
+        }
+    }
+}
+
+impl Plugin for OuterShellPlugin {
+    fn build(&self, app: &mut App) {
+        if self.headless {
+            app.add_plugins(HeadlessPlugin);
+        } else {
+            app.add_plugins(
+                    DefaultPlugins
+                        .set(WindowPlugin {
+                            primary_window: Some(Window {
+                                title: self.title.clone(),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        })
+                        .build()
+                        .add_before::<bevy::asset::AssetPlugin>(EmbeddedAssetPlugin { mode: bevy_embedded_assets::PluginMode::ReplaceDefault }),
+                )
+                .add_plugins(InputManagerPlugin::<PlayerAction>::default())
+                // Global so menus can read input while there is no player
+                .init_resource::<ActionState<PlayerAction>>()
+                .insert_resource(Controls::load())
+                .add_systems(PreUpdate, apply_controls.run_if(resource_changed::<Controls>))
+                .insert_resource(ClearColor(Color::srgb(0.04, 0.005, 0.04)))
+                .add_plugins(ShapePlugin)
+                .add_plugins(ParallaxPlugin)
+                .add_systems(OnEnter(AppState::Setup), setup)
+                .add_systems(Update, transition_to_in_menu.run_if(in_state(AppState::Setup)).run_if(pending_assets_loaded))
+                .add_plugins(MainMenuPlugin)
+                .add_plugins(HighScorePlugin)
+                .add_plugins(SettingsPlugin)
+                .add_plugins(SoundPlugin)
+                .add_plugins(MusicPlugin)
+                .add_plugins(SelectionPlugin)
+                // Always run while game is running
+                .add_systems(Update, (pause_control.run_if(in_state(SettingsState::Closed)), zoom_control).run_if(in_state(AppState::InGame)))
+                .add_systems(FixedUpdate, player_control.run_if(not(resource_exists::<Playback>)).in_set(GameplaySet::Input))
+                // Only run when unpaused
+                .add_systems(Update,
+                    camera_follow
+                        .before(ParallaxSystems)
+                        .run_if(game_not_paused)
+                        .run_if(in_state(AppState::InGame)),
+                );
+
+            if self.subsystems.hud {
+                app.add_plugins(HudPlugin);
+            }
+        }
+
+        if self.subsystems.enemies {
+            app.add_plugins(EnemyPlugin);
+        }
+        if self.subsystems.space_objects {
+            app.add_plugins(ObjectPlugin);
+        }
+        if self.subsystems.replay {
+            app.add_plugins(ReplayPlugin { playback: self.replay.clone() });
+        }
+
+        app.init_state::<AppState>()
+            .init_state::<GameState>()
+            .add_plugins(UpgradePlugin)
+            .add_plugins(SavePlugin)
+            .add_plugins(TurretPlugin)
+            .add_event::<TakeDamageEvent>()
+            .add_event::<KillEvent>()
+            .add_event::<SoundEvent>()
+            .init_resource::<GameRng>()
+            .init_resource::<SpatialIndex>()
+            .init_resource::<PendingAssets>()
+            .insert_resource(RunSeed(self.seed))
+            .insert_resource(StartingLoadout(self.starting_loadout.clone()))
+            .insert_resource(Time::<Fixed>::from_hz(SIMULATION_HZ))
+            // Single threaded so systems always run in the same order, keeping runs reproducible from their seed
+            .edit_schedule(FixedUpdate, |schedule| {
+                schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+            })
+            .configure_sets(FixedUpdate,
+                (
+                    GameplaySet::Input,
+                    GameplaySet::Ai,
+                    GameplaySet::Engine,
+                    GameplaySet::Physics,
+                    GameplaySet::Collision,
+                    GameplaySet::Damage,
+                    GameplaySet::Death,
+                    GameplaySet::Loot,
+                )
+                    .chain()
+                    .run_if(game_not_paused)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            // InGame
+            .add_systems(OnEnter(AppState::InGame), (setup_new_game, setup_player))
+            // Simulation
+            .add_systems(FixedUpdate,
+                (
+                    game_time_system.in_set(GameplaySet::Input),
+                    seeker_system.in_set(GameplaySet::Ai),
+                    engine_system.in_set(GameplaySet::Engine),
+                    (
+                        shockwave_system,
+                        (physics_system, rotator_system, expanding_collider_system),
+                        spatial_index_system,
+                    )
+                        .chain()
+                        .in_set(GameplaySet::Physics),
+                    (bullet_system, bullet_collision_system).chain().in_set(GameplaySet::Collision),
+                    (status_effect_system, take_damage_events, combat_system).chain().in_set(GameplaySet::Damage),
+                    (death_system, kill_stats_system).chain().in_set(GameplaySet::Death),
+                    (loot_magnet_system, loot_cargo_collision, level_up_system, run_stats_system).chain().in_set(GameplaySet::Loot),
+                ),
+            )
+            // Smooth movement between simulation steps
+            .add_systems(FixedFirst, store_previous_translation_system)
+            .add_systems(RunFixedMainLoop,
+                (
+                    restore_translation_system.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+                    interpolate_translation_system.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+                ),
+            )
+            // Rendering only, run every frame while unpaused
+            .add_systems(Update,
+                (
+                    laser_render_system,
+                    explosion_render_system,
+                    hit_flash_system,
+                    floating_text_system,
+                    status_overlay_system,
+                )
+                    .distributive_run_if(game_not_paused)
+                    .distributive_run_if(in_state(AppState::InGame)),
+            )
+            // Cleanup
+            .add_systems(OnExit(AppState::InGame), reset_game)
+            .add_systems(OnEnter(AppState::Restarting), restart_run);
+    }
+}
+
+fn game_not_paused(game_state: Res<State<GameState>>) -> bool {
+    *game_state.get() != GameState::Paused && *game_state.get() != GameState::Selection
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut create_parallax: EventWriter<CreateParallaxEvent>) {
+    // Set the font
+    commands.insert_resource(Fonts {
+        primary: asset_server.load("fonts/AnonymousPro-Regular.ttf"),
+        unicode: asset_server.load("fonts/DejaVuLGCSansMono.ttf"),
+    });
+
+    
+    // Spawn the Camera
+    let camera = commands
+        .spawn((
+            Camera2d,
+            MainCamera,
+            CameraShake::default(),
+            Bloom {
+                intensity: BLOOM_INTENSITY,
+                composite_mode: BloomCompositeMode::Additive,
+                ..Default::default()
+            },
+        ))
+        .insert(ParallaxCameraComponent::default())
+        .id();
+
+    // Setup parralax
+    create_parallax.send(CreateParallaxEvent {
+        layers_data: vec![
+            LayerData {
+                speed: LayerSpeed::Bidirectional(0.95, 0.95),
+                path: "nebula-tile.png".to_string(),
+                tile_size: UVec2::new(1024, 1024),
+                scale: Vec2::splat(5.0),
+                z: RenderLayer::Background.as_z_with_offset(-10.),
+                ..default()
+            },
+            LayerData {
+                speed: LayerSpeed::Bidirectional(0.9, 0.9),
+                path: "stars-tile.png".to_string(),
+                tile_size: UVec2::new(1024, 1024),
+                z: RenderLayer::Background.as_z(),
+                ..default()
+            },
+        ],
+        camera,
+    });
+}
+
+fn transition_to_in_menu(mut app_state: ResMut<NextState<AppState>>) {
+    app_state.set(AppState::Menu);
+}
+
+
+fn setup_new_game(
+    mut commands: Commands,
+    run_seed: Res<RunSeed>,
+    starting_loadout: Res<StartingLoadout>,
+    playback: Option<Res<Playback>>,
+    resume: Option<Res<ResumeRun>>,
+    mut rng: ResMut<GameRng>,
+    mut upgrade_event: EventWriter<UpgradeEvent>,
+) {
+    // Seed the run so it can be reproduced
+    let seed = playback
+        .as_ref()
+        .map(|playback| playback.replay.seed)
+        .or(run_seed.0)
+        .unwrap_or_else(rand::random);
+    *rng = GameRng::from_seed(seed);
+
+    // Replays start with the loadout they were recorded with
+    let loadout = playback
+        .map(|playback| playback.replay.loadout.clone())
+        .unwrap_or_else(|| starting_loadout.0.clone());
+    // A resumed run already has its weapons
+    if resume.is_none() {
+        upgrade_event.send_batch(loadout.iter().copied());
+    }
+
+    // Set the start time
+    commands.insert_resource(GameTime::default());
+
+    // Create point count
+    commands.insert_resource(Points { value: 0 });
+    commands.insert_resource(RunStats::default());
+
+    // Start player at level 0 so they get immediate selection, unless they already have a loadout
+    commands.insert_resource(PlayerLevel { value: if loadout.is_empty() { 0 } else { 1 } });
+}
+
+// Spawn the player
+fn setup_player(mut commands: Commands, fonts: Res<Fonts>) {
+    // The ship has no input map of its own, the saved controls drive the global one in `apply_controls`
+    commands
+        .spawn((
+            ShipBundle {
+                glyph: GlyphBundle::new("V", Colour::PLAYER, 20.0, fonts.primary.clone()),
+                transform: Transform::from_translation(Vec3 {
+                    x: 100.0,
+                    y: 100.0,
+                    z: RenderLayer::Player.as_z(),
+                }),
+                physics: Physics { mass: 2.0, ..Physics::new(5.0) },
+                engine: Engine::new_with_steering(8.0, 16.0, 10.0),
+                health: Health::new(100, 100),
+                collider: Collider { radius: 10.0 },
+                targettable: Targettable(Allegiance::PLAYER),
+                will_target: WillTarget(vec![Allegiance::ENEMY]),
+                ..Default::default()
+            },
+            BaseGlyphRotation {
+                rotation: Quat::from_rotation_z(PI / 2.0),
+            },
+            IsPlayer,
+            Cargo::default(),
+            Magnet::default(),
+        ));
+}
+
+fn game_time_system(time: Res<Time>, mut game_time: ResMut<GameTime>) {
+    game_time.0.tick(time.delta());
+}
+
+fn reset_game(
+    mut commands: Commands,
+    query: Query<Entity, With<DespawnWithScene>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.insert_resource(PlayerUpgrades::default());
+    commands.insert_resource(BanishedUpgrades::default());
+    commands.insert_resource(SelectionCharges::default());
+    next_game_state.set(GameState::Running);
+}
+
+fn restart_run(mut app_state: ResMut<NextState<AppState>>) {
+    app_state.set(AppState::InGame);
+}
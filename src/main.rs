@@ -1,230 +1,25 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod util;
-mod component;
-mod plugin;
-mod resource;
-mod system;
-mod input;
+use std::path::PathBuf;
 
+use ascii::OuterShellPlugin;
 use bevy::prelude::*;
-use bevy_embedded_assets::EmbeddedAssetPlugin;
-use bevy_parallax::CreateParallaxEvent;
-use bevy_parallax::{LayerData, LayerSpeed, ParallaxCameraComponent, ParallaxPlugin, ParallaxSystems};
-use bevy_prototype_lyon::prelude::*;
-use component::*;
-use input::PlayerAction;
-use leafwing_input_manager::plugin::InputManagerPlugin;
-use leafwing_input_manager::InputManagerBundle;
-use plugin::EnemyPlugin;
-use plugin::HudPlugin;
-use plugin::TurretPlugin;
-use plugin::UpgradePlugin;
-use plugin::MainMenuPlugin;
-use plugin::SelectionPlugin;
-use plugin::ObjectPlugin;
-use util::RenderLayer;
-use util::Colour;
-use resource::*;
-use std::f32::consts::PI;
-use system::*;
-use bevy::core_pipeline::bloom::{Bloom, BloomCompositeMode};
-
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
-enum AppState {
-    #[default]
-    Setup,
-    Menu,
-    InGame,
-}
-
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
-pub enum GameState {
-    #[default]
-    Running,
-    Selection,
-    Paused,
-    GameOver,
-}
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
     App::new()
-        .add_plugins(
-            DefaultPlugins
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: String::from("Outer Shell"),
-                        ..Default::default()
-                    }),
-                    ..Default::default()
-                })
-                .build()
-                .add_before::<bevy::asset::AssetPlugin>(EmbeddedAssetPlugin { mode: bevy_embedded_assets::PluginMode::ReplaceDefault }),
-        )
-        .add_plugins(InputManagerPlugin::<PlayerAction>::default())
-        .insert_resource(ClearColor(Color::srgb(0.04, 0.005, 0.04)))
-        .add_plugins(ShapePlugin)
-        .add_plugins(ParallaxPlugin)
-        .init_state::<AppState>()
-        .init_state::<GameState>()
-        .add_systems(OnEnter(AppState::Setup), setup)
-        .add_systems(Update, transition_to_in_menu.run_if(in_state(AppState::Setup)))
-        .add_plugins(MainMenuPlugin)
-        .add_plugins(SelectionPlugin)
-        .add_plugins(UpgradePlugin)
-        .add_plugins(TurretPlugin)
-        .add_plugins(HudPlugin)
-        .add_plugins(EnemyPlugin)
-        .add_plugins(ObjectPlugin)
-        .add_event::<TakeDamageEvent>()
-        // InGame
-        .add_systems(OnEnter(AppState::InGame), (setup_new_game, setup_player))
-        // Always run while game is running
-        .add_systems(Update, (pause_control, zoom_control).run_if(in_state(AppState::InGame)))
-        // Only run when unpaused
-        .add_systems(Update,
-            (
-                game_time_system,
-                physics_system,
-                engine_system,
-                player_control,
-                camera_follow.before(ParallaxSystems),
-                bullet_system,
-                bullet_collision_system,
-                combat_system,
-                laser_render_system,
-                explosion_render_system,
-                expanding_collider_system,
-                death_system,
-                loot_magnet_system,
-                loot_cargo_collision,
-                seeker_system,
-            )
-                .distributive_run_if(game_not_paused)
-                .distributive_run_if(in_state(AppState::InGame)),
-        )
-        .add_systems(Update,
-            (
-                level_up_system,
-                take_damage_events,
-                hit_flash_system,
-                floating_text_system,
-                rotator_system,
-            )
-                .distributive_run_if(game_not_paused)
-                .distributive_run_if(in_state(AppState::InGame)),
-        )
-        // Cleanup
-        .add_systems(OnExit(AppState::InGame), reset_game)
+        .add_plugins(OuterShellPlugin {
+            headless: args.iter().any(|arg| arg == "--headless"),
+            seed: arg_value(&args, "--seed").and_then(|seed| seed.parse().ok()),
+            replay: arg_value(&args, "--replay").map(PathBuf::from),
+            ..Default::default()
+        })
         .run();
 }
 
-fn game_not_paused(game_state: Res<State<GameState>>) -> bool {
-    *game_state.get() != GameState::Paused && *game_state.get() != GameState::Selection
-}
-
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut create_parallax: EventWriter<CreateParallaxEvent>) {
-    // Set the font
-    commands.insert_resource(Fonts {
-        primary: asset_server.load("fonts/AnonymousPro-Regular.ttf"),
-        unicode: asset_server.load("fonts/DejaVuLGCSansMono.ttf"),
-    });
-
-    
-    // Spawn the Camera
-    let camera = commands
-        .spawn((
-            Camera2d,
-            MainCamera,
-            CameraShake::default(),
-            Bloom {
-                intensity: 0.15,
-                composite_mode: BloomCompositeMode::Additive,
-                ..Default::default()
-            },
-        ))
-        .insert(ParallaxCameraComponent::default())
-        .id();
-
-    // Setup parralax
-    create_parallax.send(CreateParallaxEvent {
-        layers_data: vec![
-            LayerData {
-                speed: LayerSpeed::Bidirectional(0.95, 0.95),
-                path: "nebula-tile.png".to_string(),
-                tile_size: UVec2::new(1024, 1024),
-                scale: Vec2::splat(5.0),
-                z: RenderLayer::Background.as_z_with_offset(-10.),
-                ..default()
-            },
-            LayerData {
-                speed: LayerSpeed::Bidirectional(0.9, 0.9),
-                path: "stars-tile.png".to_string(),
-                tile_size: UVec2::new(1024, 1024),
-                z: RenderLayer::Background.as_z(),
-                ..default()
-            },
-        ],
-        camera,
-    });
-}
-
-fn transition_to_in_menu(mut app_state: ResMut<NextState<AppState>>) {
-    app_state.set(AppState::Menu);
-}
-
-
-fn setup_new_game(mut commands: Commands) {
-    // Set the start time
-    commands.insert_resource(GameTime::default());
-
-    // Create point count
-    commands.insert_resource(Points { value: 0 });
-
-    // Start player at level 0 so they get immediate selection
-    commands.insert_resource(PlayerLevel { value: 0 });
-}
-
-// Spawn the player
-fn setup_player(mut commands: Commands, fonts: Res<Fonts>) {
-    commands
-        .spawn((
-            ShipBundle {
-                glyph: GlyphBundle::new("V", Colour::PLAYER, 20.0, fonts.primary.clone()),
-                transform: Transform::from_translation(Vec3 {
-                    x: 100.0,
-                    y: 100.0,
-                    z: RenderLayer::Player.as_z(),
-                }),
-                physics: Physics::new(5.0),
-                engine: Engine::new_with_steering(8.0, 16.0, 10.0),
-                health: Health::new(100, 100),
-                collider: Collider { radius: 10.0 },
-                targettable: Targettable(Allegiance::PLAYER),
-                will_target: WillTarget(vec![Allegiance::ENEMY]),
-                ..Default::default()
-            },
-            BaseGlyphRotation {
-                rotation: Quat::from_rotation_z(PI / 2.0),
-            },
-            IsPlayer,
-            Cargo::default(),
-            Magnet::default(),
-            InputManagerBundle::with_map(PlayerAction::default_input_map()),
-        ));
-}
-
-fn game_time_system(time: Res<Time>, mut game_time: ResMut<GameTime>) {
-    game_time.0.tick(time.delta());
-}
-
-fn reset_game(
-    mut commands: Commands,
-    query: Query<Entity, With<DespawnWithScene>>,
-    mut next_game_state: ResMut<NextState<GameState>>,
-) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
-    }
-    next_game_state.set(GameState::Running);
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a String> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
 }
@@ -5,6 +5,13 @@ mod turret;
 mod hud;
 mod enemy;
 mod object;
+mod headless;
+mod replay;
+mod save;
+mod high_score;
+mod settings;
+mod sound;
+mod music;
 
 pub use menu::*;
 pub use selection::*;
@@ -12,4 +19,11 @@ pub use upgrade::*;
 pub use turret::*;
 pub use hud::*;
 pub use enemy::*;
-pub use object::*;
\ No newline at end of file
+pub use object::*;
+pub use headless::*;
+pub use replay::*;
+pub use save::*;
+pub use high_score::*;
+pub use settings::*;
+pub use sound::*;
+pub use music::*;
\ No newline at end of file
@@ -1,21 +1,14 @@
-mod fighter;
-mod drone;
-mod drone_boss;
-mod mothership;
-mod final_boss;
-
-use self::fighter::*;
-use self::drone::*;
-use self::drone_boss::*;
-use self::mothership::*;
-use self::final_boss::*;
+mod archetype;
+
+pub use self::archetype::*;
 
 use std::{cmp::min, time::Duration};
 
 use bevy::prelude::*;
-use rand::Rng;
 
-use crate::{component::*, resource::*, util::*, AppState, GameState, game_not_paused};
+use crate::{component::*, resource::*, util::*, AppState, GameState, GameplaySet};
+
+use super::ResumeRun;
 
 #[derive(Resource)]
 pub struct Spawning {
@@ -29,44 +22,65 @@ pub struct AI;
 #[derive(Component)]
 pub struct FinalBoss;
 
+/// Gets a health bar on the hud while alive
+#[derive(Component)]
+pub struct Boss {
+    pub name: String,
+}
+
+/// Seconds between waves before the difficulty speeds them up
+pub const SPAWN_INTERVAL: f32 = 30.0;
+
+/// Seconds into the run when the final boss arrives
+pub const FINAL_BOSS_TIME: f32 = 60.0 * 10.0;
+
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::InGame), spawn_startup)
-            .add_systems(
-                Update,
-                ai_system
-                    .run_if(game_not_paused)
-                    .run_if(in_state(AppState::InGame)),
-            )
-            // Stop when game over
-            .add_systems(Update,
-                (spawner_system, spawn_final_boss_system)
-                    .distributive_run_if(in_state(GameState::Running))
-                    .distributive_run_if(in_state(AppState::InGame)),
+        app.init_asset::<EnemyArchetypes>()
+            .register_asset_loader(RonAssetLoader::<EnemyArchetypes>::default())
+            .add_systems(Startup, load_enemy_archetypes)
+            .add_systems(OnEnter(AppState::InGame), spawn_startup.run_if(not(resource_exists::<ResumeRun>)))
+            .add_systems(FixedUpdate,
+                (
+                    // Stop when game over
+                    (spawner_system, spawn_final_boss_system).run_if(in_state(GameState::Running)),
+                    ai_system,
+                )
+                    .chain()
+                    .in_set(GameplaySet::Ai),
             );
     }
 }
 
+fn load_enemy_archetypes(mut commands: Commands, asset_server: Res<AssetServer>, mut pending: ResMut<PendingAssets>) {
+    let handle = asset_server.load::<EnemyArchetypes>("enemies.ron");
+    pending.0.push(handle.clone().untyped());
+    commands.insert_resource(EnemyArchetypesHandle(handle));
+}
+
 fn spawn_startup(mut commands: Commands) {
-    // Set spawn limit
-    let seconds = 30.0;
-    let mut timer = Timer::from_seconds(seconds, TimerMode::Repeating);
-    timer.set_elapsed(Duration::from_secs_f32(seconds));
+    // Set spawn limit, the first wave arrives straight away
+    let mut timer = Timer::from_seconds(SPAWN_INTERVAL, TimerMode::Repeating);
+    timer.set_elapsed(Duration::from_secs_f32(SPAWN_INTERVAL));
     commands.insert_resource(Spawning { max: 100, timer });
 }
 
 fn spawner_system(
     mut commands: Commands,
     fonts: Res<Fonts>,
+    archetypes: Res<Assets<EnemyArchetypes>>,
+    archetypes_handle: Res<EnemyArchetypesHandle>,
     time: Res<Time>,
     game_time: Res<GameTime>,
     mut spawning: ResMut<Spawning>,
+    mut rng: ResMut<GameRng>,
     enemies_query: Query<Entity, With<AI>>,
     player_query: Query<&Transform, With<IsPlayer>>,
 ) {
-    let difficulty = game_time.0.elapsed_secs() as u32 / 30 + 1; // Goes from 1-20 difficulty in 10 minutes
+    let Some(archetypes) = archetypes.get(&archetypes_handle.0) else { return; };
+    let difficulty = game_time.difficulty();
 
     spawning.timer.tick(time.delta() * difficulty); // Spawns quicker as time goes on
 
@@ -75,7 +89,7 @@ fn spawner_system(
             // pick a random location off screen from player
             const DISTANCE_OFFSCREEN: f32 = 1000.0;
             let spawn_point = player_transformation.translation.truncate()
-                + Math::random_2d_unit_vector() * DISTANCE_OFFSCREEN;
+                + Math::random_2d_unit_vector(&mut *rng) * DISTANCE_OFFSCREEN;
 
             // Get current total amount of enemies
             let num_enemies: u32 = enemies_query
@@ -88,45 +102,39 @@ fn spawner_system(
 
             for _ in 0..max_num_enemies_to_spawn {
                 // Ensure they spawn in a pack not on top of eachother
-                let jiggled_spawn = spawn_point + Math::random_2d_unit_vector() * 10.0;
-                let spawn_func = match rand::thread_rng().gen_range(0..100) {
-                    0 => spawn_mothership,
-                    1..=5 => spawn_drone_boss,
-                    6..=15 => spawn_fighter,
-                    _ => spawn_drone,
-                };
-                spawn_func(
-                    &mut commands,
-                    &fonts,
-                    jiggled_spawn.extend(RenderLayer::Enemy.as_z()),
-                );
-                
+                let jiggled_spawn = spawn_point + Math::random_2d_unit_vector(&mut *rng) * 10.0;
+                if let Some(archetype) = archetypes.pick(&mut *rng) {
+                    spawn_enemy(
+                        &mut commands,
+                        &fonts,
+                        archetype,
+                        jiggled_spawn.extend(RenderLayer::Enemy.as_z()),
+                    );
+                }
             }
         }
     }
 }
 
 fn ai_system(
+    index: Res<SpatialIndex>,
     mut query: Query<(&Transform, &mut Engine, Entity), (With<AI>, With<Transform>, With<Engine>)>,
-    other_query: Query<(&Transform, &Physics, Entity), (With<AI>, With<Transform>, With<Physics>)>,
+    other_query: Query<&Transform, (With<AI>, With<Transform>, With<Physics>)>,
     player_query: Query<&Transform, (With<IsPlayer>, With<Transform>, Without<AI>)>,
 ) {
     const PROXIMITY_CUTOFF: f32 = 20.0;
     const LOOK_AHEAD: f32 = 10.0;
+    const NEIGHBOUR_DISTANCE: f32 = 50.0;
     if let Ok(player_transform) = player_query.get_single() {
         for (transform, mut engine, entity) in &mut query {
-            let neighbours: Vec<Vec2> = other_query
+            let position = transform.translation.truncate();
+            let neighbours: Vec<Vec2> = index
+                .query_circle(&[SpatialLayer::Enemy], position, NEIGHBOUR_DISTANCE)
                 .iter()
-                .filter(|other| other.2 != entity)
-                .filter(|other| {
-                    other
-                        .0
-                        .translation
-                        .truncate()
-                        .distance(transform.translation.truncate())
-                        < 50.0
-                })
-                .map(|other| other.0.translation.truncate())
+                .filter(|other| other.entity != entity)
+                .filter_map(|other| other_query.get(other.entity).ok())
+                .map(|other| other.translation.truncate())
+                .filter(|other| other.distance(position) < NEIGHBOUR_DISTANCE)
                 .collect();
             let to_target =
                 player_transform.translation.truncate() - transform.translation.truncate();
@@ -165,16 +173,21 @@ fn seperation(position: Vec2, neighbours: &Vec<Vec2>) -> Vec2 {
 fn spawn_final_boss_system(
     mut commands: Commands,
     fonts: Res<Fonts>,
+    archetypes: Res<Assets<EnemyArchetypes>>,
+    archetypes_handle: Res<EnemyArchetypesHandle>,
     game_time: Res<GameTime>,
+    mut rng: ResMut<GameRng>,
     query: Query<(), With<FinalBoss>>,
     player_query: Query<&Transform, With<IsPlayer>>,
 ) {
-    if game_time.0.elapsed_secs() > 60.0 * 10.0 {
+    if game_time.0.elapsed_secs() > FINAL_BOSS_TIME {
         if query.is_empty() {
             // Spawn final boss
             let pos = player_query.get_single().map(|transform| transform.translation.truncate()).unwrap_or_default();
-            let spawn_point = pos + Math::random_2d_unit_vector() * 1000.0;
-            spawn_final_boss(&mut commands, &fonts, spawn_point.extend(RenderLayer::Enemy.as_z()))
+            let spawn_point = pos + Math::random_2d_unit_vector(&mut *rng) * 1000.0;
+            let Some(final_boss) = archetypes.get(&archetypes_handle.0).and_then(|archetypes| archetypes.get("final_boss")) else { return; };
+            let entity = spawn_enemy(&mut commands, &fonts, final_boss, spawn_point.extend(RenderLayer::Enemy.as_z()));
+            commands.entity(entity).insert(FinalBoss);
         }
     }
 }
@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{util::Colour, resource::Fonts, component::*};
+
+use super::{Boss, AI};
+
+/// Every enemy that can appear in a run, loaded from `enemies.ron`
+#[derive(Asset, TypePath, Deserialize)]
+pub struct EnemyArchetypes {
+    pub enemies: Vec<EnemyArchetype>,
+}
+
+impl EnemyArchetypes {
+    pub fn get(&self, name: &str) -> Option<&EnemyArchetype> {
+        self.enemies.iter().find(|enemy| enemy.name == name)
+    }
+
+    /// Random archetype where the chance of each is its share of the total spawn weight
+    pub fn pick(&self, rng: &mut impl Rng) -> Option<&EnemyArchetype> {
+        let total: u32 = self.enemies.iter().map(|enemy| enemy.spawn_weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.gen_range(0..total);
+        for enemy in &self.enemies {
+            if roll < enemy.spawn_weight {
+                return Some(enemy);
+            }
+            roll -= enemy.spawn_weight;
+        }
+        None
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EnemyArchetype {
+    pub name: String,
+    pub glyph: String,
+    pub size: f32,
+    /// Degrees to turn the glyph so it faces forward
+    #[serde(default)]
+    pub rotation: f32,
+    pub drag: f32,
+    /// Defaults to 1 when missing or not positive, heavier ships are knocked back less
+    #[serde(default)]
+    pub mass: Option<f32>,
+    pub engine: EnemyEngine,
+    pub health: i32,
+    #[serde(default)]
+    pub shield: i32,
+    #[serde(default)]
+    pub resistance: Option<Resistance>,
+    pub radius: f32,
+    #[serde(default)]
+    pub explosion: Option<EnemyExplosion>,
+    #[serde(default)]
+    pub points: Option<u32>,
+    #[serde(default)]
+    pub drops_loot: bool,
+    /// Zero means it is never picked by the spawner
+    #[serde(default)]
+    pub spawn_weight: u32,
+    #[serde(default)]
+    pub turrets: Vec<EnemyTurret>,
+    /// Name shown on its health bar, only bosses have one
+    #[serde(default)]
+    pub boss: Option<String>,
+}
+
+impl EnemyArchetype {
+    pub fn physics(&self) -> Physics {
+        Physics { mass: self.mass.filter(|mass| *mass > 0.0).unwrap_or(1.0), ..Physics::new(self.drag) }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EnemyEngine {
+    pub power: f32,
+    pub max_speed: f32,
+    #[serde(default)]
+    pub method: EngineMethod,
+}
+
+#[derive(Deserialize)]
+pub struct EnemyExplosion {
+    pub size_min: f32,
+    pub size_max: f32,
+}
+
+#[derive(Deserialize)]
+pub struct EnemyTurret {
+    pub class: TurretClass,
+    #[serde(default)]
+    pub range: Option<f32>,
+    pub fire_rate: f32,
+    pub damage: i32,
+    #[serde(default)]
+    pub shots: Option<u8>,
+    #[serde(default)]
+    pub size: Option<f32>,
+    #[serde(default)]
+    pub status: Option<StatusEffect>,
+}
+
+/// Archetype an enemy was spawned from
+#[derive(Component)]
+pub struct ArchetypeName(pub String);
+
+#[derive(Resource)]
+pub struct EnemyArchetypesHandle(pub Handle<EnemyArchetypes>);
+
+pub fn spawn_enemy(commands: &mut Commands, fonts: &Fonts, archetype: &EnemyArchetype, position: Vec3) -> Entity {
+    let mut enemy = commands.spawn((
+        ShipBundle {
+            glyph: GlyphBundle::new(&archetype.glyph, Colour::ENEMY, archetype.size, fonts.primary.clone()),
+            transform: Transform::from_translation(position),
+            physics: archetype.physics(),
+            engine: Engine {
+                power: archetype.engine.power,
+                max_speed: archetype.engine.max_speed,
+                method: archetype.engine.method,
+                ..Default::default()
+            },
+            health: Health::new(archetype.health, archetype.shield),
+            collider: Collider { radius: archetype.radius },
+            explodes_on_despawn: archetype
+                .explosion
+                .as_ref()
+                .map(|explosion| ExplodesOnDespawn {
+                    size_min: explosion.size_min,
+                    size_max: explosion.size_max,
+                    ..Default::default()
+                })
+                .unwrap_or_default(),
+            ..Default::default()
+        },
+        BaseGlyphRotation {
+            rotation: Quat::from_rotation_z(archetype.rotation.to_radians()),
+        },
+        AI,
+        ArchetypeName(archetype.name.clone()),
+    ));
+
+    if archetype.drops_loot {
+        enemy.insert(DropsLoot);
+    }
+    if let Some(value) = archetype.points {
+        enemy.insert(WorthPoints { value });
+    }
+    if let Some(resistance) = archetype.resistance {
+        enemy.insert(resistance);
+    }
+    if let Some(name) = &archetype.boss {
+        enemy.insert(Boss { name: name.clone() });
+    }
+
+    enemy.with_children(|parent| {
+        for turret in &archetype.turrets {
+            parent.spawn(TurretBundle {
+                class: turret.class,
+                range: turret.range.map(|max| Range { max }).unwrap_or_default(),
+                fire_rate: FireRate::from_rate_in_seconds(turret.fire_rate),
+                damage: DoesDamage::from_amount(turret.damage),
+                shots: turret.shots.map(|amount| MultiShot { amount }).unwrap_or_default(),
+                size: EffectSize(turret.size.unwrap_or_default()),
+                status: AppliesStatus(turret.status),
+                ..Default::default()
+            });
+        }
+    });
+
+    enemy.id()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn archetypes(weights: &[u32]) -> EnemyArchetypes {
+        EnemyArchetypes {
+            enemies: weights
+                .iter()
+                .enumerate()
+                .map(|(index, weight)| {
+                    ron::from_str(&format!(
+                        "(name: \"{}\", glyph: \"c\", size: 1.0, drag: 1.0, engine: (power: 1.0, max_speed: 1.0), health: 1, radius: 1.0, spawn_weight: {})",
+                        index, weight
+                    ))
+                    .expect("archetype should parse")
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn enemies_ron_matches_original_stats() {
+        let archetypes: EnemyArchetypes = ron::from_str(include_str!("../../../assets/enemies.ron")).expect("enemies.ron should parse");
+        // Name, glyph, size, health, shield, radius, engine power and max speed, turret and its damage
+        let original = [
+            ("drone", "c", 18.0, 1, 4, 10.0, 10.0, 10.0, TurretClass::BlastLaser, 1),
+            ("fighter", "w", 18.0, 10, 0, 10.0, 14.0, 14.0, TurretClass::AutoCannon, 2),
+            ("drone_boss", "C", 32.0, 10, 40, 30.0, 8.0, 8.0, TurretClass::BlastLaser, 1),
+            ("mothership", "@", 60.0, 100, 80, 50.0, 3.0, 3.0, TurretClass::RocketLauncher, 5),
+            ("final_boss", "Œ", 50.0, 1000, 4000, 50.0, 40.0, 80.0, TurretClass::PierceLaser, 5),
+        ];
+        for (name, glyph, size, health, shield, radius, power, max_speed, turret, damage) in original {
+            let archetype = archetypes.get(name).unwrap_or_else(|| panic!("missing {}", name));
+            assert_eq!(archetype.glyph, glyph, "{} glyph", name);
+            assert_eq!(archetype.size, size, "{} size", name);
+            assert_eq!(archetype.health, health, "{} health", name);
+            assert_eq!(archetype.shield, shield, "{} shield", name);
+            assert_eq!(archetype.radius, radius, "{} radius", name);
+            assert_eq!(archetype.engine.power, power, "{} engine power", name);
+            assert_eq!(archetype.engine.max_speed, max_speed, "{} max speed", name);
+            assert!(archetype.turrets.first().is_some_and(|first| first.class == turret && first.damage == damage), "{} turret", name);
+            assert!(archetype.turrets.iter().all(|turret| turret.status.is_none()), "{} turret status", name);
+            assert!(archetype.mass.unwrap_or(1.0) > 0.0, "{} mass", name);
+        }
+        // The final boss is only spawned on its timer
+        assert_eq!(archetypes.get("final_boss").unwrap().spawn_weight, 0);
+    }
+
+    #[test]
+    fn pick_without_weight_is_none() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(archetypes(&[]).pick(&mut rng).is_none());
+        assert!(archetypes(&[0, 0]).pick(&mut rng).is_none());
+    }
+
+    #[test]
+    fn pick_skips_zero_weights_and_reaches_the_last_bucket() {
+        let archetypes = archetypes(&[0, 3, 0, 1, 0]);
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut counts = [0; 5];
+        for _ in 0..1000 {
+            let picked = archetypes.pick(&mut rng).expect("total weight is not zero");
+            counts[picked.name.parse::<usize>().unwrap()] += 1;
+        }
+        assert_eq!(counts[0] + counts[2] + counts[4], 0);
+        assert!(counts[3] > 0, "last weighted bucket was never picked");
+        assert!(counts[1] > counts[3]);
+    }
+
+    #[test]
+    fn single_archetype_is_always_picked() {
+        let archetypes = archetypes(&[0, 1]);
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..100 {
+            assert_eq!(archetypes.pick(&mut rng).unwrap().name, "1");
+        }
+    }
+}
@@ -1,43 +0,0 @@
-use std::f32::consts::PI;
-
-use bevy::prelude::*;
-
-use crate::{util::Colour, resource::Fonts, component::*};
-
-use super::AI;
-
-pub fn spawn_drone(commands: &mut Commands, fonts: &Res<Fonts>, position: Vec3) {
-    commands
-        .spawn((
-            ShipBundle {
-                glyph: GlyphBundle::new("c", Colour::ENEMY, 18.0, fonts.primary.clone()),
-                transform: Transform::from_translation(position),
-                physics: Physics::new(5.0),
-                engine: Engine::new(10.0, 10.0),
-                health: Health::new(1, 4),
-                collider: Collider { radius: 10.0 },
-                explodes_on_despawn: ExplodesOnDespawn {
-                    size_min: 15.0,
-                    size_max: 20.0,
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
-            BaseGlyphRotation {
-                rotation: Quat::from_rotation_z(-PI),
-            },
-            AI,
-            DropsLoot,
-            WorthPoints { value: 10 },
-        ))
-        .with_children(|parent| {
-            // Custom short range blast laser
-            parent.spawn(TurretBundle {
-                class: TurretClass::BlastLaser,
-                range: Range { max: 100.0 },
-                fire_rate: FireRate::from_rate_in_seconds(2.0),
-                damage: DoesDamage::from_amount(1),
-                ..Default::default()
-            });
-        });
-}
\ No newline at end of file
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use bevy::{app::{AppExit, ScheduleRunnerPlugin}, asset::AssetPlugin, log::LogPlugin, prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+use bevy_embedded_assets::{EmbeddedAssetPlugin, PluginMode};
+
+use crate::{resource::*, AppState, GameState, SIMULATION_HZ};
+
+use super::{roll_options, BanishedUpgrades, OfferedUpgrades, Playback, PlayerUpgrades, Recording, SelectionChoice, UpgradeEvent};
+
+/// Runs the game simulation without a window, renderer or input.
+/// Each update advances a fixed step so a whole run completes as fast as the cpu allows.
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+                MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+                LogPlugin::default(),
+                StatesPlugin,
+                EmbeddedAssetPlugin { mode: PluginMode::ReplaceDefault },
+                AssetPlugin::default(),
+            ))
+            // One simulation step per update
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / SIMULATION_HZ)))
+            // Nothing is rendered so the fonts are never loaded
+            .insert_resource(Fonts {
+                primary: Handle::default(),
+                unicode: Handle::default(),
+            })
+            .add_systems(Update, transition_to_in_game.run_if(in_state(AppState::Setup)).run_if(pending_assets_loaded))
+            .add_systems(OnEnter(GameState::Selection), auto_select)
+            .add_systems(OnEnter(GameState::GameOver), report_and_exit);
+    }
+}
+
+fn transition_to_in_game(mut app_state: ResMut<NextState<AppState>>) {
+    app_state.set(AppState::InGame);
+}
+
+// Without a selection screen always take the first option offered
+#[allow(clippy::too_many_arguments)]
+fn auto_select(
+    player_level: Res<PlayerLevel>,
+    upgrades: Res<PlayerUpgrades>,
+    banished: Res<BanishedUpgrades>,
+    mut offered: ResMut<OfferedUpgrades>,
+    mut rng: ResMut<GameRng>,
+    playback: Option<Res<Playback>>,
+    recording: Option<ResMut<Recording>>,
+    mut upgrade_event: EventWriter<UpgradeEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Always roll so the rng advances the same as with the selection screen
+    offered.0 = roll_options(&mut *rng, &player_level, &upgrades, &banished);
+    // Replays pick the recorded option instead
+    if playback.is_some() {
+        return;
+    }
+    if let Some(upgrade) = offered.0.first() {
+        if let Some(mut recording) = recording {
+            recording.0.selections.push(SelectionChoice::Pick(*upgrade));
+        }
+        upgrade_event.send(*upgrade);
+    }
+    next_state.set(GameState::Running);
+}
+
+fn report_and_exit(
+    points: Res<Points>,
+    game_time: Res<GameTime>,
+    player_level: Res<PlayerLevel>,
+    rng: Res<GameRng>,
+    stats: Res<RunStats>,
+    mut exit: EventWriter<AppExit>,
+) {
+    info!(
+        "Game over after {:0>2}:{:0>2} at level {} with {} points (seed {})",
+        game_time.0.elapsed().as_secs() / 60,
+        game_time.0.elapsed().as_secs() % 60,
+        player_level.value,
+        points.value,
+        rng.seed,
+    );
+    for (class, weapon) in stats.weapons_by_damage() {
+        info!("{}: {} damage, {} kills", class, weapon.damage, weapon.kills);
+    }
+    exit.send(AppExit::Success);
+}
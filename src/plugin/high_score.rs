@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{resource::*, util::Storage, GameState};
+
+use super::{Playback, PlayerUpgrades, UpgradeEvent};
+
+const HIGH_SCORE_FILE: &str = "highscores.ron";
+
+const MAX_HIGH_SCORES: usize = 10;
+
+/// Summary of a finished run
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HighScore {
+    pub points: u32,
+    /// Seconds survived
+    pub time: f32,
+    pub level: u32,
+    pub upgrades: Vec<(UpgradeEvent, u8)>,
+    /// Seconds since the unix epoch
+    pub date: u64,
+}
+
+impl HighScore {
+    pub fn display_time(&self) -> String {
+        let seconds = self.time as u32;
+        format!("{:0>2}:{:0>2}", seconds / 60, seconds % 60)
+    }
+
+    /// Year-month-day of the run in UTC
+    pub fn display_date(&self) -> String {
+        // Days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+        let days = (self.date / 86_400) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        format!("{}-{:0>2}-{:0>2}", year, month, day)
+    }
+
+    pub fn display_weapons(&self) -> String {
+        self.upgrades
+            .iter()
+            .filter(|(upgrade, _)| matches!(upgrade, UpgradeEvent::Weapon(_)))
+            .map(|(upgrade, level)| format!("{} {}", upgrade, level))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Best runs, highest points first
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+    /// Position of the run that just finished, if it made the table
+    #[serde(skip)]
+    pub latest: Option<usize>,
+}
+
+impl HighScores {
+    pub fn load() -> HighScores {
+        if !Storage::exists(HIGH_SCORE_FILE) {
+            return HighScores::default();
+        }
+        match Storage::read(HIGH_SCORE_FILE).and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string())) {
+            Ok(high_scores) => high_scores,
+            Err(err) => {
+                warn!("Could not load high scores from {}: {}", HIGH_SCORE_FILE, err);
+                HighScores::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|err| err.to_string())?;
+        Storage::write(HIGH_SCORE_FILE, &contents)
+    }
+
+    /// Position of the run in the table, if it was good enough to stay
+    pub fn submit(&mut self, high_score: HighScore) -> Option<usize> {
+        let position = self.entries.iter().position(|entry| high_score.points > entry.points).unwrap_or(self.entries.len());
+        self.entries.insert(position, high_score);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        (position < MAX_HIGH_SCORES).then_some(position)
+    }
+}
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load())
+            // Replays are not new runs
+            .add_systems(OnEnter(GameState::GameOver), record_high_score.run_if(not(resource_exists::<Playback>)))
+            .add_systems(OnExit(GameState::GameOver), clear_latest);
+    }
+}
+
+pub fn record_high_score(
+    mut high_scores: ResMut<HighScores>,
+    points: Res<Points>,
+    game_time: Res<GameTime>,
+    player_level: Res<PlayerLevel>,
+    upgrades: Res<PlayerUpgrades>,
+) {
+    let mut run_upgrades: Vec<(UpgradeEvent, u8)> = upgrades.0.iter().map(|(upgrade, level)| (*upgrade, *level)).collect();
+    run_upgrades.sort_by(|a, b| b.1.cmp(&a.1));
+    high_scores.latest = high_scores.submit(HighScore {
+        points: points.value,
+        time: game_time.0.elapsed_secs(),
+        level: player_level.value,
+        upgrades: run_upgrades,
+        date: Storage::now(),
+    });
+    if let Err(err) = high_scores.save() {
+        warn!("Could not save high scores to {}: {}", HIGH_SCORE_FILE, err);
+    }
+}
+
+fn clear_latest(mut high_scores: ResMut<HighScores>) {
+    high_scores.latest = None;
+}
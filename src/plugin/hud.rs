@@ -1,8 +1,31 @@
+use std::collections::HashSet;
+
 use bevy::prelude::*;
 
 use crate::{resource::{Fonts, PlayerLevel, GameTime}, component::*, util::Colour, AppState};
 
-use super::PlayerUpgrades;
+use super::{PlayerUpgrades, SpaceObject, AI, Boss, FinalBoss, FINAL_BOSS_TIME};
+
+/// Width and height of the radar in pixels
+const RADAR_SIZE: f32 = 150.0;
+
+/// World distance from the player to the edge of the radar
+const RADAR_RANGE: f32 = 2000.0;
+
+/// Loot within the same square of this size shows as one blip
+const LOOT_CLUSTER_SIZE: f32 = 200.0;
+
+/// Enemies worth at least this many points get an arrow when off screen
+const HIGH_VALUE_POINTS: u32 = 50;
+
+/// Distance of the threat arrows from the edge of the screen
+const ARROW_MARGIN: f32 = 24.0;
+
+/// Most boss health bars shown at once, nearest first
+const MAX_BOSS_BARS: usize = 3;
+
+/// Seconds of warning before the final boss arrives
+const BOSS_WARNING_SECONDS: f32 = 5.0;
 
 pub struct HudPlugin;
 
@@ -11,15 +34,29 @@ impl Plugin for HudPlugin {
         app
             .add_systems(OnEnter(AppState::InGame), setup_hud)
             // Always run while game is running
-            .add_systems(Update, hud_system.run_if(in_state(AppState::InGame)));
+            .add_systems(Update, (hud_system, radar_system, threat_arrow_system, boss_warning_system).run_if(in_state(AppState::InGame)));
     }
 }
 
+#[derive(Component)]
+struct Radar;
+
+#[derive(Component)]
+struct RadarBlip;
+
+/// Points toward a dangerous enemy outside the camera view
+#[derive(Component)]
+struct ThreatArrow;
+
+#[derive(Component)]
+struct BossWarning;
+
 #[derive(Component)]
 pub enum UINode {
     Status,
     Equipment,
     Upgrades,
+    Bosses,
 }
 
 // Spawn the hud
@@ -117,6 +154,76 @@ fn setup_hud(mut commands: Commands, fonts: Res<Fonts>) {
                 ));
             }
         });
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(0.0),
+            bottom: Val::Px(0.0),
+            width: Val::Px(RADAR_SIZE),
+            height: Val::Px(RADAR_SIZE),
+            margin: UiRect::all(Val::Px(5.0)),
+            border: UiRect::all(Val::Px(1.0)),
+            ..default()
+        },
+        BackgroundColor(Colour::BLACK.with_alpha(0.6)),
+        BorderColor(Colour::INACTIVE),
+        Radar,
+        DespawnWithScene,
+    ));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(5.0),
+                width: Val::Percent(100.0),
+                column_gap: Val::Px(2.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            UINode::Bosses,
+            DespawnWithScene,
+        ))
+        .with_children(|parent| {
+            for _ in 0..MAX_BOSS_BARS {
+                for colour in [Colour::YELLOW, Colour::RED, Colour::SHIELD] {
+                    parent.spawn((
+                        Text("".to_string()),
+                        TextFont { font_size: 12.0, font: fonts.primary.clone(), ..Default::default()},
+                        TextColor(colour),
+                    ));
+                }
+            }
+        });
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(25.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+            BossWarning,
+            DespawnWithScene,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text("!! WARNING !!".to_string()),
+                TextFont { font_size: 40.0, font: fonts.primary.clone(), ..Default::default()},
+                TextColor(Colour::RED),
+            ));
+            parent.spawn((
+                Text("Massive signature approaching".to_string()),
+                TextFont { font_size: 16.0, font: fonts.primary.clone(), ..Default::default()},
+                TextColor(Colour::RED),
+            ));
+        });
 }
 
 fn bar(current: i32, max: i32, width: i32) -> String {
@@ -130,16 +237,18 @@ fn bar(current: i32, max: i32, width: i32) -> String {
     format!("{}{}", String::from('|').repeat(bars), String::from('.').repeat(width as usize - bars))
 }
   
+#[allow(clippy::too_many_arguments)]
 pub fn hud_system(
     upgrades: Res<PlayerUpgrades>,
-    player_query: Query<(&Engine, &Health, &Cargo, &Children), With<IsPlayer>>,
+    player_query: Query<(&Engine, &Health, &Cargo, &Children, &Transform), With<IsPlayer>>,
     turret_query: Query<(&FireRate, &TurretClass)>,
+    boss_query: Query<(&Boss, &Health, &Transform, Has<FinalBoss>)>,
     mut query: Query<(&Children, &UINode)>,
     mut q_child: Query<&mut Text>,
     level: Res<PlayerLevel>,
     game_time: Res<GameTime>,
 ) {
-    if let Ok((engine, health, cargo, turrets)) = player_query.get_single() {
+    if let Ok((engine, health, cargo, turrets, player_transform)) = player_query.get_single() {
         // Loop over children and update display values
         for (children, ui_node) in &mut query {
 
@@ -166,6 +275,27 @@ pub fn hud_system(
                     display.resize_with(10, Default::default);
                     display
                 }
+                UINode::Bosses => {
+                    // Final boss first then the closest
+                    let mut bosses: Vec<_> = boss_query.iter().collect();
+                    bosses.sort_by(|(_, _, a, a_final), (_, _, b, b_final)| {
+                        b_final.cmp(a_final).then(
+                            a.translation.distance(player_transform.translation)
+                                .total_cmp(&b.translation.distance(player_transform.translation)),
+                        )
+                    });
+                    let mut display = bosses
+                        .into_iter()
+                        .take(MAX_BOSS_BARS)
+                        .flat_map(|(boss, health, _, _)| [
+                            boss.name.to_uppercase(),
+                            format!("{:<8} {} {}", "Armor", bar(health.health, health.max_health, 30), health.health),
+                            format!("{:<8} {} {}", "Shield", bar(health.shield, health.max_shield, 30), health.shield),
+                        ])
+                        .collect::<Vec<String>>();
+                    display.resize_with(MAX_BOSS_BARS * 3, Default::default);
+                    display
+                }
             };
 
             for (i, display) in displays.iter().enumerate() {
@@ -177,4 +307,159 @@ pub fn hud_system(
             }
         }
     }
-}
\ No newline at end of file
+}
+fn radar_system(
+    mut commands: Commands,
+    radar_query: Query<Entity, With<Radar>>,
+    mut blip_query: Query<(&mut Node, &mut BackgroundColor), With<RadarBlip>>,
+    player_query: Query<&Transform, With<IsPlayer>>,
+    enemy_query: Query<(&Transform, Has<FinalBoss>), With<AI>>,
+    object_query: Query<&Transform, With<SpaceObject>>,
+    loot_query: Query<&Transform, With<IsLoot>>,
+) {
+    let (Ok(radar), Ok(player)) = (radar_query.get_single(), player_query.get_single()) else { return; };
+    let centre = player.translation.truncate();
+
+    // Offset from the player, colour, size and whether to pin it to the edge when out of range
+    let mut blips: Vec<(Vec2, Color, f32, bool)> = vec![];
+    for transform in &object_query {
+        blips.push((transform.translation.truncate() - centre, Colour::INACTIVE, 5.0, false));
+    }
+    let clusters: HashSet<IVec2> = loot_query
+        .iter()
+        .map(|transform| (transform.translation.truncate() / LOOT_CLUSTER_SIZE).floor().as_ivec2())
+        .collect();
+    for cluster in clusters {
+        let position = (cluster.as_vec2() + 0.5) * LOOT_CLUSTER_SIZE;
+        blips.push((position - centre, Colour::PURPLE, 3.0, false));
+    }
+    for (transform, boss) in &enemy_query {
+        let offset = transform.translation.truncate() - centre;
+        match boss {
+            true => blips.push((offset, Colour::RED, 7.0, true)),
+            false => blips.push((offset, Colour::ENEMY, 3.0, false)),
+        }
+    }
+    blips.push((Vec2::ZERO, Colour::PLAYER, 4.0, false));
+
+    let scale = RADAR_SIZE / 2.0 / RADAR_RANGE;
+    let mut visible = blips.into_iter().filter_map(|(offset, colour, size, pinned)| {
+        let offset = match pinned {
+            true => offset.clamp_length_max(RADAR_RANGE),
+            false if offset.length() > RADAR_RANGE => return None,
+            false => offset,
+        };
+        let position = Vec2::new(offset.x, -offset.y) * scale + RADAR_SIZE / 2.0 - size / 2.0;
+        Some((position, colour, size))
+    });
+
+    // Reuse the blips from last frame, hiding any left over
+    for (mut node, mut background) in &mut blip_query {
+        match visible.next() {
+            Some((position, colour, size)) => {
+                node.display = Display::Flex;
+                node.left = Val::Px(position.x);
+                node.top = Val::Px(position.y);
+                node.width = Val::Px(size);
+                node.height = Val::Px(size);
+                background.0 = colour;
+            }
+            None => node.display = Display::None,
+        }
+    }
+    commands.entity(radar).with_children(|parent| {
+        for (position, colour, size) in visible {
+            parent.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(position.x),
+                    top: Val::Px(position.y),
+                    width: Val::Px(size),
+                    height: Val::Px(size),
+                    ..default()
+                },
+                BackgroundColor(colour),
+                RadarBlip,
+            ));
+        }
+    });
+}
+
+fn threat_arrow_system(
+    mut commands: Commands,
+    fonts: Res<Fonts>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    threat_query: Query<(&Transform, Has<FinalBoss>, Option<&WorthPoints>), With<AI>>,
+    mut arrow_query: Query<(&mut Node, &mut Text, &mut TextColor, &mut Visibility), With<ThreatArrow>>,
+) {
+    let Ok((camera, camera_transform)) = camera_query.get_single() else { return; };
+    let Some(size) = camera.logical_viewport_size() else { return; };
+    let half = size / 2.0;
+    let inner = (half - ARROW_MARGIN).max(Vec2::ONE);
+
+    let mut arrows = threat_query
+        .iter()
+        .filter(|(_, boss, points)| *boss || points.is_some_and(|points| points.value >= HIGH_VALUE_POINTS))
+        .filter_map(|(transform, boss, _)| {
+            let position = camera.world_to_viewport(camera_transform, transform.translation).ok()?;
+            if position.cmpge(Vec2::ZERO).all() && position.cmple(size).all() {
+                return None;
+            }
+            // Slide along the line from the centre of the screen until it meets the margin
+            let direction = position - half;
+            let scale = (inner.x / direction.x.abs()).min(inner.y / direction.y.abs());
+            let glyph = match direction.x.abs() / inner.x > direction.y.abs() / inner.y {
+                true if direction.x > 0.0 => ">",
+                true => "<",
+                false if direction.y > 0.0 => "v",
+                false => "^",
+            };
+            let colour = if boss { Colour::RED } else { Colour::YELLOW };
+            Some((half + direction * scale, glyph, colour))
+        });
+
+    for (mut node, mut text, mut text_colour, mut visibility) in &mut arrow_query {
+        match arrows.next() {
+            Some((position, glyph, colour)) => {
+                node.left = Val::Px(position.x - 8.0);
+                node.top = Val::Px(position.y - 8.0);
+                text.0 = glyph.to_string();
+                text_colour.0 = colour;
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+    for (position, glyph, colour) in arrows {
+        commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(position.x - 8.0),
+                top: Val::Px(position.y - 8.0),
+                ..default()
+            },
+            Text(glyph.to_string()),
+            TextFont { font_size: 16.0, font: fonts.primary.clone(), ..Default::default()},
+            TextColor(colour),
+            ThreatArrow,
+            DespawnWithScene,
+        ));
+    }
+}
+
+/// Flash a banner in the last few seconds before the final boss arrives
+fn boss_warning_system(
+    game_time: Res<GameTime>,
+    final_boss: Query<(), With<FinalBoss>>,
+    mut query: Query<&mut Visibility, With<BossWarning>>,
+) {
+    let elapsed = game_time.0.elapsed_secs();
+    let incoming = final_boss.is_empty() && (FINAL_BOSS_TIME - BOSS_WARNING_SECONDS..FINAL_BOSS_TIME).contains(&elapsed);
+    let flash = elapsed.fract() < 0.6;
+    for mut visibility in &mut query {
+        *visibility = match incoming && flash {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        };
+    }
+}
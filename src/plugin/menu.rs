@@ -1,12 +1,16 @@
-use bevy::{app::AppExit, prelude::*};
+use bevy::{app::AppExit, prelude::*, ui::FocusPolicy};
 
-use crate::{resource::*, AppState, GameState};
+use crate::{input::{Binding, Controls, PlayerAction, BINDING_SLOTS}, resource::*, util::Colour, AppState, GameState};
+
+use super::{record_high_score, HighScores, PlayerUpgrades, ResumeRun, SaveGame, Setting, Settings, SettingsState};
 
 #[derive(Resource, Default)]
 struct MenuData {
     main: Option<Entity>,
     pause: Option<Entity>,
     game_over: Option<Entity>,
+    settings: Option<Entity>,
+    controls: Option<Entity>,
 }
 
 #[derive(Component)]
@@ -14,10 +18,36 @@ struct MenuButton(pub ButtonAction);
 
 enum ButtonAction {
     Play,
+    Resume,
+    Restart,
+    Continue,
     Exit,
     ToTitle,
+    OpenSettings,
+    CloseSettings,
+    Adjust(Setting, f32),
+    OpenControls,
+    /// Action and which of its bindings to change
+    Rebind(PlayerAction, usize),
+    ResetControls,
 }
 
+/// Text showing the current value of a setting
+#[derive(Component)]
+struct SettingValue(Setting);
+
+/// Text showing one of the bindings of an action
+#[derive(Component)]
+struct BindingText(PlayerAction, usize);
+
+/// Prompts and conflicts on the controls screen
+#[derive(Component)]
+struct ControlsStatus;
+
+/// Waiting for the next button press to bind to one slot of an action
+#[derive(Resource)]
+struct Capturing(PlayerAction, usize);
+
 const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
 const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
 
@@ -32,15 +62,30 @@ impl Plugin for MainMenuPlugin {
             .add_systems(OnExit(AppState::Menu), cleanup_menu)
 
             .add_systems(OnEnter(GameState::Paused), setup_paused)
+            .add_systems(Update, menu.run_if(in_state(GameState::Paused)))
             .add_systems(OnExit(GameState::Paused), cleanup_pause)
 
-            .add_systems(OnEnter(GameState::GameOver), setup_game_over)
+            .add_systems(OnEnter(GameState::GameOver), setup_game_over.after(record_high_score))
             .add_systems(Update, menu.run_if(in_state(GameState::GameOver)))
-            .add_systems(OnExit(GameState::GameOver), cleanup_game_over);
+            .add_systems(OnExit(GameState::GameOver), cleanup_game_over)
+
+            // Opened from the main menu or pause screen
+            .add_systems(OnEnter(SettingsState::Open), setup_settings)
+            .add_systems(Update, setting_value_system.run_if(in_state(SettingsState::Open)))
+            .add_systems(OnExit(SettingsState::Open), cleanup_settings)
+            .add_systems(OnEnter(SettingsState::Controls), setup_controls)
+            .add_systems(Update,
+                (capture_binding.run_if(resource_exists::<Capturing>), binding_text_system)
+                    .chain()
+                    .run_if(in_state(SettingsState::Controls)),
+            )
+            .add_systems(OnExit(SettingsState::Controls), cleanup_controls)
+            .add_systems(OnExit(AppState::Menu), close_settings)
+            .add_systems(OnExit(GameState::Paused), close_settings);
     }
 }
 
-fn setup_menu(mut commands: Commands, fonts: Res<Fonts>, mut menu_data: ResMut<MenuData>) {
+fn setup_menu(mut commands: Commands, fonts: Res<Fonts>, high_scores: Res<HighScores>, mut menu_data: ResMut<MenuData>) {
     let root_entity = commands
         .spawn(Node {
             width: Val::Percent(100.0),
@@ -52,16 +97,62 @@ fn setup_menu(mut commands: Commands, fonts: Res<Fonts>, mut menu_data: ResMut<M
             ..default()
         })
         .with_children(|parent| {
+            if SaveGame::exists() {
+                button(parent, &fonts, "Continue", ButtonAction::Continue);
+            }
             button(parent, &fonts, "Play", ButtonAction::Play);
+            button(parent, &fonts, "Settings", ButtonAction::OpenSettings);
             #[cfg(not(target_arch = "wasm32"))]
             button(parent, &fonts, "Exit", ButtonAction::Exit);
+
+            if !high_scores.entries.is_empty() {
+                parent.spawn((
+                    Text("Best Runs".to_owned()),
+                    TextFont { font_size: 24.0, font: fonts.primary.clone(), ..Default::default()},
+                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                    Node { margin: UiRect::top(Val::Px(20.0)), ..default() },
+                ));
+            }
+            for (index, high_score) in high_scores.entries.iter().enumerate() {
+                parent
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text(format!(
+                                "{:0>2} {:>8} points  {}  level {:>2}  {}",
+                                index + 1,
+                                high_score.points,
+                                high_score.display_time(),
+                                high_score.level,
+                                high_score.display_date(),
+                            )),
+                            TextFont { font_size: 16.0, font: fonts.primary.clone(), ..Default::default()},
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                        ));
+                        parent.spawn((
+                            Text(high_score.display_weapons()),
+                            TextFont { font_size: 12.0, font: fonts.primary.clone(), ..Default::default()},
+                            TextColor(Colour::INACTIVE),
+                        ));
+                    });
+            }
         })
         .id();
     menu_data.main = Some(root_entity);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn menu(
+    mut commands: Commands,
     mut next_state: ResMut<NextState<AppState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+    mut settings: ResMut<Settings>,
+    mut controls: ResMut<Controls>,
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, &MenuButton),
         (Changed<Interaction>, With<Button>, With<MenuButton>),
@@ -73,8 +164,33 @@ fn menu(
             Interaction::Pressed => {
                 match button.0 {
                     ButtonAction::Play => next_state.set(AppState::InGame),
+                    ButtonAction::Resume => next_game_state.set(GameState::Running),
+                    ButtonAction::Restart => next_state.set(AppState::Restarting),
+                    ButtonAction::Continue => match SaveGame::load() {
+                        Ok(save) => {
+                            commands.insert_resource(ResumeRun(save));
+                            next_state.set(AppState::InGame);
+                        }
+                        Err(err) => error!("Could not load save: {}", err),
+                    },
                     ButtonAction::Exit => { exit.send(AppExit::Success); },
                     ButtonAction::ToTitle => next_state.set(AppState::Menu),
+                    ButtonAction::OpenSettings => next_settings_state.set(SettingsState::Open),
+                    ButtonAction::CloseSettings => next_settings_state.set(SettingsState::Closed),
+                    ButtonAction::Adjust(setting, direction) => {
+                        settings.adjust(setting, direction);
+                        if let Err(err) = settings.save() {
+                            warn!("Could not save settings: {}", err);
+                        }
+                    }
+                    ButtonAction::OpenControls => next_settings_state.set(SettingsState::Controls),
+                    ButtonAction::Rebind(action, slot) => commands.insert_resource(Capturing(action, slot)),
+                    ButtonAction::ResetControls => {
+                        *controls = Controls::default();
+                        if let Err(err) = controls.save() {
+                            warn!("Could not save controls: {}", err);
+                        }
+                    }
                 }
             }
             Interaction::Hovered => {
@@ -108,6 +224,19 @@ fn cleanup_game_over(commands: Commands, mut menu_data: ResMut<MenuData>) {
     cleanup(commands, &mut menu_data.game_over);
 }
 
+fn cleanup_settings(commands: Commands, mut menu_data: ResMut<MenuData>) {
+    cleanup(commands, &mut menu_data.settings);
+}
+
+fn cleanup_controls(mut commands: Commands, mut menu_data: ResMut<MenuData>) {
+    commands.remove_resource::<Capturing>();
+    cleanup(commands, &mut menu_data.controls);
+}
+
+fn close_settings(mut next_settings_state: ResMut<NextState<SettingsState>>) {
+    next_settings_state.set(SettingsState::Closed);
+}
+
 fn button(parent: &mut ChildBuilder, fonts: &Res<Fonts>, text: &str, action: ButtonAction) {
     parent
         .spawn((
@@ -131,19 +260,44 @@ fn button(parent: &mut ChildBuilder, fonts: &Res<Fonts>, text: &str, action: But
         });
 }
 
-fn setup_paused(mut commands: Commands, fonts: Res<Fonts>, mut menu_data: ResMut<MenuData>) {
+#[allow(clippy::too_many_arguments)]
+fn setup_paused(
+    mut commands: Commands,
+    fonts: Res<Fonts>,
+    controls: Res<Controls>,
+    upgrades: Res<PlayerUpgrades>,
+    game_time: Res<GameTime>,
+    points: Res<Points>,
+    player_level: Res<PlayerLevel>,
+    rng: Res<GameRng>,
+    mut menu_data: ResMut<MenuData>,
+) {
+    let resume_key = controls.bindings(PlayerAction::Pause).first().map(|binding| binding.label()).unwrap_or_else(|| "Unbound".to_owned());
+    let mut loadout = upgrades.display_for_ui();
+    loadout.sort();
+    let seconds = game_time.0.elapsed_secs() as u32;
+    let stats = [
+        format!("Time   {:0>2}:{:0>2}", seconds / 60, seconds % 60),
+        format!("Points {}", points.value),
+        format!("Level  {}", player_level.value),
+        format!("Seed   {}", rng.seed),
+    ];
+
     let root_entity = commands
-        .spawn(Node {
-            width: Val::Percent(100.0),
-            height: Val::Percent(100.0),
-            position_type: PositionType::Absolute,
-            padding: UiRect::top(Val::Px(10.0)),
-            justify_content: JustifyContent::Start,
-            align_items: AlignItems::Center,
-            flex_direction: FlexDirection::Column,
-            row_gap: Val::Px(10.0),
-            ..default()
-        })
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                padding: UiRect::top(Val::Px(10.0)),
+                justify_content: JustifyContent::Start,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+        ))
         .with_children(|parent| {
             parent.spawn((
                 Text("Paused".to_owned()),
@@ -151,15 +305,81 @@ fn setup_paused(mut commands: Commands, fonts: Res<Fonts>, mut menu_data: ResMut
                 TextColor(Color::srgb(0.9, 0.9, 0.9)),
             ));
             parent.spawn((
-                Text("Click <Escape> To Resume".to_owned()),
+                Text(format!("Press <{}> To Resume", resume_key)),
                 TextFont { font_size: 16.0, font: fonts.primary.clone(), ..Default::default()},
                 TextColor(Color::srgb(0.9, 0.9, 0.9)),
             ));
+            button(parent, &fonts, "Resume", ButtonAction::Resume);
+            button(parent, &fonts, "Restart Run", ButtonAction::Restart);
+            button(parent, &fonts, "Settings", ButtonAction::OpenSettings);
+            button(parent, &fonts, "Return To Title", ButtonAction::ToTitle);
+            #[cfg(not(target_arch = "wasm32"))]
+            button(parent, &fonts, "Exit", ButtonAction::Exit);
+
+            parent
+                .spawn(Node {
+                    margin: UiRect::top(Val::Px(20.0)),
+                    column_gap: Val::Px(60.0),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    stat_column(parent, &fonts, "Loadout", loadout);
+                    stat_column(parent, &fonts, "Run", stats.to_vec());
+                });
         }).id();
     menu_data.pause = Some(root_entity);
 }
 
-fn setup_game_over(mut commands: Commands, fonts: Res<Fonts>, mut menu_data: ResMut<MenuData>, points: Res<Points>) {
+/// Titled list of lines for summaries
+fn stat_column(parent: &mut ChildBuilder, fonts: &Res<Fonts>, title: &str, lines: Vec<String>) {
+    parent
+        .spawn(Node { flex_direction: FlexDirection::Column, row_gap: Val::Px(4.0), ..default() })
+        .with_children(|parent| {
+            parent.spawn((
+                Text(title.to_owned()),
+                TextFont { font_size: 20.0, font: fonts.primary.clone(), ..Default::default()},
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            ));
+            for line in lines {
+                parent.spawn((
+                    Text(line),
+                    TextFont { font_size: 14.0, font: fonts.primary.clone(), ..Default::default()},
+                    TextColor(Colour::INACTIVE),
+                ));
+            }
+        });
+}
+
+fn setup_game_over(
+    mut commands: Commands,
+    fonts: Res<Fonts>,
+    mut menu_data: ResMut<MenuData>,
+    points: Res<Points>,
+    rng: Res<GameRng>,
+    high_scores: Res<HighScores>,
+    stats: Res<RunStats>,
+) {
+    let mut weapons = vec![format!("{:<16} {:>7} {:>6}", "", "Damage", "Kills")];
+    weapons.extend(
+        stats
+            .weapons_by_damage()
+            .into_iter()
+            .map(|(class, weapon)| format!("{:<16} {:>7} {:>6}", class.to_string(), weapon.damage, weapon.kills)),
+    );
+    let mut run: Vec<String> = [
+        ("Shield damage taken", stats.shield_damage_taken.to_string()),
+        ("Armour damage taken", stats.armour_damage_taken.to_string()),
+        ("Loot collected", stats.loot_collected.to_string()),
+        ("Bonus cargo", stats.bonus_cargo.to_string()),
+        ("Distance travelled", format!("{:.0}", stats.distance)),
+        ("Most enemies at once", stats.peak_enemies.to_string()),
+    ]
+    .into_iter()
+    .map(|(label, value)| format!("{:<20} {:>6}", label, value))
+    .collect();
+    run.push(String::new());
+    run.extend(stats.kills_by_archetype().into_iter().map(|(name, count)| format!("{:<20} {:>6}", name.replace('_', " "), count)));
+
     let root_entity = commands
         .spawn(Node {
             height: Val::Percent(100.0),
@@ -177,8 +397,283 @@ fn setup_game_over(mut commands: Commands, fonts: Res<Fonts>, mut menu_data: Res
                 TextFont { font_size: 30.0, font: fonts.primary.clone(), ..Default::default()},
                 TextColor(Color::srgb(0.9, 0.9, 0.9)),
             ));
+            match high_scores.latest {
+                Some(0) => {
+                    parent.spawn((
+                        Text("New record!".to_owned()),
+                        TextFont { font_size: 24.0, font: fonts.primary.clone(), ..Default::default()},
+                        TextColor(Colour::YELLOW),
+                    ));
+                }
+                Some(position) => {
+                    parent.spawn((
+                        Text(format!("#{} best run", position + 1)),
+                        TextFont { font_size: 16.0, font: fonts.primary.clone(), ..Default::default()},
+                        TextColor(Colour::YELLOW),
+                    ));
+                }
+                None => (),
+            }
+            parent
+                .spawn(Node { column_gap: Val::Px(60.0), margin: UiRect::vertical(Val::Px(10.0)), ..default() })
+                .with_children(|parent| {
+                    stat_column(parent, &fonts, "Weapons", weapons);
+                    stat_column(parent, &fonts, "Run", run);
+                });
+            parent.spawn((
+                Text(format!("Seed {}", rng.seed)),
+                TextFont { font_size: 16.0, font: fonts.primary.clone(), ..Default::default()},
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            ));
             button(parent, &fonts, "Return To Title", ButtonAction::ToTitle);
         })
         .id();
     menu_data.game_over = Some(root_entity);
 }
+
+fn small_button(parent: &mut ChildBuilder, fonts: &Res<Fonts>, text: &str, action: ButtonAction) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                min_width: Val::Px(40.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            BackgroundColor(NORMAL_BUTTON),
+            MenuButton(action),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text(text.to_string()),
+                TextFont { font_size: 24.0, font: fonts.primary.clone(), ..Default::default()},
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            ));
+        });
+}
+
+/// Full screen panel covering whichever menu opened it
+fn overlay() -> impl Bundle {
+    (
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            position_type: PositionType::Absolute,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(10.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.04, 0.005, 0.04, 0.95)),
+        FocusPolicy::Block,
+        GlobalZIndex(1),
+    )
+}
+
+fn setup_settings(mut commands: Commands, fonts: Res<Fonts>, settings: Res<Settings>, mut menu_data: ResMut<MenuData>) {
+    let root_entity = commands
+        .spawn(overlay())
+        .with_children(|parent| {
+            parent.spawn((
+                Text("Settings".to_owned()),
+                TextFont { font_size: 30.0, font: fonts.primary.clone(), ..Default::default()},
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            ));
+            for setting in Setting::ALL {
+                parent
+                    .spawn(Node {
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text(setting.label().to_string()),
+                            TextFont { font_size: 24.0, font: fonts.primary.clone(), ..Default::default()},
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                            Node { width: Val::Px(250.0), ..default() },
+                        ));
+                        small_button(parent, &fonts, "<", ButtonAction::Adjust(setting, -1.0));
+                        parent.spawn((
+                            Text(settings.display(setting)),
+                            TextFont { font_size: 24.0, font: fonts.primary.clone(), ..Default::default()},
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                            TextLayout::new_with_justify(JustifyText::Center),
+                            Node { width: Val::Px(100.0), ..default() },
+                            SettingValue(setting),
+                        ));
+                        small_button(parent, &fonts, ">", ButtonAction::Adjust(setting, 1.0));
+                    });
+            }
+            button(parent, &fonts, "Controls", ButtonAction::OpenControls);
+            button(parent, &fonts, "Back", ButtonAction::CloseSettings);
+        })
+        .id();
+    menu_data.settings = Some(root_entity);
+}
+
+fn setting_value_system(settings: Res<Settings>, mut query: Query<(&mut Text, &SettingValue)>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut text, value) in &mut query {
+        text.0 = settings.display(value.0);
+    }
+}
+
+fn slot_label(controls: &Controls, action: PlayerAction, slot: usize) -> String {
+    controls.bindings(action).get(slot).map(|binding| binding.label()).unwrap_or_else(|| "-".to_owned())
+}
+
+/// Button showing one binding of an action, pressing it rebinds just that one
+fn slot_button(parent: &mut ChildBuilder, fonts: &Res<Fonts>, controls: &Controls, action: PlayerAction, slot: usize) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(150.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            BackgroundColor(NORMAL_BUTTON),
+            MenuButton(ButtonAction::Rebind(action, slot)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text(slot_label(controls, action, slot)),
+                TextFont { font_size: 16.0, font: fonts.primary.clone(), ..Default::default()},
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                BindingText(action, slot),
+            ));
+        });
+}
+
+fn setup_controls(mut commands: Commands, fonts: Res<Fonts>, controls: Res<Controls>, mut menu_data: ResMut<MenuData>) {
+    let label = |parent: &mut ChildBuilder, label: &str| {
+        parent.spawn((
+            Text(label.to_owned()),
+            TextFont { font_size: 20.0, font: fonts.primary.clone(), ..Default::default()},
+            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            Node { width: Val::Px(200.0), ..default() },
+        ));
+    };
+
+    let root_entity = commands
+        .spawn(overlay())
+        .with_children(|parent| {
+            parent.spawn((
+                Text("Controls".to_owned()),
+                TextFont { font_size: 30.0, font: fonts.primary.clone(), ..Default::default()},
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            ));
+            for action in PlayerAction::REBINDABLE {
+                parent
+                    .spawn(Node { align_items: AlignItems::Center, column_gap: Val::Px(10.0), ..default() })
+                    .with_children(|parent| {
+                        label(parent, action.label());
+                        for slot in 0..BINDING_SLOTS {
+                            slot_button(parent, &fonts, &controls, action, slot);
+                        }
+                    });
+            }
+            parent
+                .spawn(Node { align_items: AlignItems::Center, column_gap: Val::Px(10.0), ..default() })
+                .with_children(|parent| {
+                    label(parent, PlayerAction::Steer.label());
+                    parent.spawn((
+                        Text("Pad Left Stick".to_owned()),
+                        TextFont { font_size: 16.0, font: fonts.primary.clone(), ..Default::default()},
+                        TextColor(Colour::INACTIVE),
+                    ));
+                });
+            parent.spawn((
+                Text::default(),
+                TextFont { font_size: 16.0, font: fonts.primary.clone(), ..Default::default()},
+                TextColor(Colour::YELLOW),
+                ControlsStatus,
+            ));
+            button(parent, &fonts, "Reset", ButtonAction::ResetControls);
+            button(parent, &fonts, "Back", ButtonAction::OpenSettings);
+        })
+        .id();
+    menu_data.controls = Some(root_entity);
+}
+
+/// Bind the next key, mouse or gamepad button pressed, Backspace clears the slot and Delete cancels
+fn capture_binding(
+    mut commands: Commands,
+    capturing: Res<Capturing>,
+    mut controls: ResMut<Controls>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut status: Query<&mut Text, With<ControlsStatus>>,
+) {
+    let (action, slot) = (capturing.0, capturing.1);
+    // The click that started capturing is not the new binding
+    if capturing.is_changed() {
+        for mut text in &mut status {
+            text.0 = format!("Press a key or button for {}, Backspace to clear, Delete to cancel", action.label());
+        }
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Backspace) {
+        commands.remove_resource::<Capturing>();
+        controls.unbind(action, slot);
+        if let Err(err) = controls.save() {
+            warn!("Could not save controls: {}", err);
+        }
+        for mut text in &mut status {
+            text.0.clear();
+        }
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Delete) {
+        commands.remove_resource::<Capturing>();
+        for mut text in &mut status {
+            text.0.clear();
+        }
+        return;
+    }
+
+    let pressed = keys
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| mouse.get_just_pressed().next().map(|button| Binding::Mouse(*button)))
+        .or_else(|| gamepads.iter().find_map(|gamepad| gamepad.get_just_pressed().next()).map(|button| Binding::Gamepad(*button)));
+    let Some(binding) = pressed else { return; };
+
+    commands.remove_resource::<Capturing>();
+    let message = match controls.conflict(action, binding) {
+        Some(other) => format!("{} is already used by {}", binding.label(), other.label()),
+        None if controls.bindings(action).contains(&binding) => format!("{} is already bound to {}", binding.label(), action.label()),
+        None => {
+            controls.bind(action, slot, binding);
+            if let Err(err) = controls.save() {
+                warn!("Could not save controls: {}", err);
+            }
+            String::new()
+        }
+    };
+    for mut text in &mut status {
+        text.0 = message.clone();
+    }
+}
+
+fn binding_text_system(controls: Res<Controls>, mut query: Query<(&mut Text, &BindingText)>) {
+    if !controls.is_changed() {
+        return;
+    }
+    for (mut text, binding) in &mut query {
+        text.0 = slot_label(&controls, binding.0, binding.1);
+    }
+}
+
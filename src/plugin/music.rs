@@ -0,0 +1,115 @@
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{resource::GameTime, AppState, GameState};
+
+use super::{Settings, SoundCategory, AI, FinalBoss};
+
+/// Fraction of full volume faded per second
+const FADE_SPEED: f32 = 0.5;
+
+/// Music volume while the game is paused or picking an upgrade
+const DUCKED: f32 = 0.3;
+
+/// Enemies alive for the pulse stem to reach full volume
+const BUSY_ENEMY_COUNT: f32 = 30.0;
+
+/// Looping layers that play in time with each other, mixed by how intense the game is
+#[derive(Component, Clone, Copy, PartialEq)]
+enum Stem {
+    Calm,
+    Pulse,
+    Drums,
+    Boss,
+}
+
+/// Current fade of a stem before the volume settings are applied
+#[derive(Component, Default)]
+struct MusicLevel(f32);
+
+#[derive(Resource)]
+struct Music {
+    stems: Vec<(Stem, Handle<AudioSource>)>,
+    game_over: Handle<AudioSource>,
+}
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_music)
+            .add_systems(Update, (start_music.run_if(not(any_with_component::<Stem>)), music_mix_system))
+            .add_systems(OnEnter(GameState::GameOver), play_game_over);
+    }
+}
+
+fn load_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Music {
+        stems: vec![
+            (Stem::Calm, asset_server.load("music/calm.wav")),
+            (Stem::Pulse, asset_server.load("music/pulse.wav")),
+            (Stem::Drums, asset_server.load("music/drums.wav")),
+            (Stem::Boss, asset_server.load("music/boss.wav")),
+        ],
+        game_over: asset_server.load("music/game_over.wav"),
+    });
+}
+
+/// Start every stem on the same frame once they have all loaded so they stay in time
+fn start_music(mut commands: Commands, music: Res<Music>, asset_server: Res<AssetServer>) {
+    if !music.stems.iter().all(|(_, handle)| asset_server.is_loaded_with_dependencies(handle.id())) {
+        return;
+    }
+    for (stem, handle) in &music.stems {
+        commands.spawn((
+            AudioPlayer::new(handle.clone()),
+            PlaybackSettings::LOOP.with_volume(Volume::new(0.0)),
+            *stem,
+            MusicLevel::default(),
+        ));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn music_mix_system(
+    time: Res<Time<Real>>,
+    settings: Res<Settings>,
+    app_state: Res<State<AppState>>,
+    game_state: Res<State<GameState>>,
+    game_time: Option<Res<GameTime>>,
+    enemies: Query<(), With<AI>>,
+    final_boss: Query<(), With<FinalBoss>>,
+    mut stems: Query<(&Stem, &mut MusicLevel, &AudioSink)>,
+) {
+    let in_game = *app_state.get() == AppState::InGame;
+    let boss = in_game && !final_boss.is_empty();
+    let difficulty = game_time.map(|game_time| game_time.difficulty()).unwrap_or(1) as f32;
+    let busy = (enemies.iter().len() as f32 / BUSY_ENEMY_COUNT).min(1.0);
+    let duck = match game_state.get() {
+        GameState::Paused | GameState::Selection => DUCKED,
+        // Make way for the game over sting
+        GameState::GameOver if in_game => 0.0,
+        _ => 1.0,
+    };
+
+    for (stem, mut level, sink) in &mut stems {
+        let target = match stem {
+            Stem::Calm if boss => 0.0,
+            Stem::Calm => 1.0,
+            Stem::Pulse if in_game => busy,
+            Stem::Drums if in_game => ((difficulty - 3.0) / 6.0).clamp(0.0, 1.0),
+            Stem::Boss if boss => 1.0,
+            _ => 0.0,
+        } * duck;
+
+        let step = FADE_SPEED * time.delta_secs();
+        level.0 += (target - level.0).clamp(-step, step);
+        sink.set_volume(level.0 * SoundCategory::Music.volume(&settings));
+    }
+}
+
+fn play_game_over(mut commands: Commands, music: Res<Music>, settings: Res<Settings>) {
+    commands.spawn((
+        AudioPlayer::new(music.game_over.clone()),
+        PlaybackSettings::DESPAWN.with_volume(Volume::new(SoundCategory::Music.volume(&settings))),
+    ));
+}
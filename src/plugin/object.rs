@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 use bevy_prototype_lyon::entity::ShapeBundle;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 use crate::*;
 
@@ -12,37 +12,52 @@ pub struct ObjectPlugin;
 impl Plugin for ObjectPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(OnEnter(AppState::InGame), setup_space_objects);
+            .add_systems(OnEnter(AppState::InGame), setup_space_objects.after(setup_new_game).run_if(not(resource_exists::<ResumeRun>)));
     }
 }
 
-pub fn generate_object_geometry(sides: i32, min_radius: f32, max_radius: f32) -> Path {
-    let mut rng = rand::thread_rng();
-    let mut path_builder = PathBuilder::new();
-    let step: f32 = 2. * PI / sides as f32;
-    path_builder.move_to(Vec2::from_angle(0.) * rng.gen_range(min_radius..max_radius));
-    for n in 1..sides+1 {
-        let angle: f32 = step * n as f32;
-        path_builder.line_to(Vec2::from_angle(angle) * rng.gen_range(min_radius..max_radius));
+/// Distance of each corner from the centre, kept so a saved object looks the same when resumed
+#[derive(Component, Clone)]
+pub struct ObjectShape(pub Vec<f32>);
+
+impl ObjectShape {
+    pub fn random(rng: &mut impl Rng, sides: i32, min_radius: f32, max_radius: f32) -> Self {
+        ObjectShape((0..=sides).map(|_| rng.gen_range(min_radius..max_radius)).collect())
+    }
+
+    pub fn path(&self) -> Path {
+        let mut path_builder = PathBuilder::new();
+        let sides = self.0.len().saturating_sub(1).max(1);
+        let step: f32 = 2. * PI / sides as f32;
+        for (n, radius) in self.0.iter().enumerate() {
+            let corner = Vec2::from_angle(step * n as f32) * *radius;
+            if n == 0 {
+                path_builder.move_to(corner);
+            } else {
+                path_builder.line_to(corner);
+            }
+        }
+        path_builder.close();
+        path_builder.build()
     }
-    path_builder.close();
-    return path_builder.build();
-  }
+}
 
-fn spawn_space_object(commands: &mut Commands) {
-    let mut rng = thread_rng();
-    let position = util::Math::random_2d_unit_vector() * 500.0;
+fn spawn_space_object(commands: &mut Commands, rng: &mut GameRng) {
+    let position = util::Math::random_2d_unit_vector(rng) * 500.0;
     let size: f32 = rng.gen_range(20.0..40.0);
+    let velocity = util::Math::random_2d_unit_vector(rng) * rng.gen_range(3.0..8.0);
+    let spin = rng.gen_range(-0.4..0.4);
+    let shape = ObjectShape::random(rng, 10, size - 10., size + 10.);
     commands
         .spawn((
             SpaceObject,
             Collider { radius: size },
-            Physics { velocity: util::Math::random_2d_unit_vector() * rng.gen_range(3.0..8.0), face_velocity: false, ..Default::default()},
-            Rotator { speed: rng.gen_range(-0.4..0.4) },
+            Physics { velocity, face_velocity: false, mass: size, ..Default::default()},
+            Rotator { speed: spin },
             Health::new(50, 0),
             Stroke::new(Colour::WHITE, 2.0),
             ShapeBundle {
-                path: generate_object_geometry(10, size-10., size+10.),
+                path: shape.path(),
                 transform: Transform::from_translation(position.extend(RenderLayer::Background.as_z())),
                 ..default()
             },
@@ -51,11 +66,13 @@ fn spawn_space_object(commands: &mut Commands) {
                 size_max: 100.0,
                 ..Default::default()
             },
+            shape,
+            DespawnWithScene,
         ));
 }
 
-fn setup_space_objects(mut commands: Commands) {
+fn setup_space_objects(mut commands: Commands, mut rng: ResMut<GameRng>) {
     for _ in 0..8 {
-        spawn_space_object(&mut commands);
+        spawn_space_object(&mut commands, &mut rng);
     }
 }
\ No newline at end of file
@@ -0,0 +1,187 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{component::*, resource::{GameRng, PlayerLevel}, system::player_control, AppState, GameState, GameplaySet};
+
+use super::{banish_option, roll_options, BanishedUpgrades, OfferedUpgrades, PlayerUpgrades, ResumeRun, SelectionChoice, SelectionCharges, SkipEvent, StartingLoadout, UpgradeEvent};
+
+/// Bump whenever the replay format or the simulation it drives changes
+pub const REPLAY_VERSION: u32 = 6;
+
+const REPLAY_FILE: &str = "replay.ron";
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub loadout: Vec<UpgradeEvent>,
+    pub frames: Vec<ReplayFrame>,
+    pub selections: Vec<SelectionChoice>,
+}
+
+/// Player input for a single simulation step
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ReplayFrame {
+    /// Where the engine was steering, none while coasting
+    pub target: Option<Vec2>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let replay: Replay = ron::from_str(&contents).map_err(|err| err.to_string())?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!("replay version {} is not supported, expected {}", replay.version, REPLAY_VERSION));
+        }
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|err| err.to_string())?;
+        std::fs::write(path, contents).map_err(|err| err.to_string())
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Recording(pub Replay);
+
+/// When present the run is driven by the replay instead of live input
+#[derive(Resource)]
+pub struct Playback {
+    pub replay: Replay,
+    pub frame: usize,
+    pub selection: usize,
+}
+
+pub struct ReplayPlugin {
+    /// Replay file to play back instead of recording a new run
+    pub playback: Option<PathBuf>,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), start_recording.run_if(not(resource_exists::<Playback>)).run_if(not(resource_exists::<ResumeRun>)))
+            .add_systems(FixedUpdate,
+                (
+                    record_frame.after(player_control).run_if(resource_exists::<Recording>),
+                    playback_frame.run_if(resource_exists::<Playback>),
+                )
+                    .in_set(GameplaySet::Input),
+            )
+            // After the options are rolled on entering the selection
+            .add_systems(Update, playback_selection.run_if(in_state(GameState::Selection)).run_if(resource_exists::<Playback>))
+            .add_systems(OnExit(AppState::InGame), finish_playback.run_if(resource_exists::<Playback>));
+
+        // Runs that are quit or restarted are kept as well as finished ones
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(OnEnter(GameState::GameOver), save_recording.run_if(resource_exists::<Recording>))
+            .add_systems(OnExit(AppState::InGame), save_recording.run_if(resource_exists::<Recording>))
+            .add_systems(Last, save_recording_on_exit.run_if(resource_exists::<Recording>));
+
+        if let Some(path) = &self.playback {
+            match Replay::load(path) {
+                Ok(replay) => {
+                    app.insert_resource(Playback { replay, frame: 0, selection: 0 })
+                        .add_systems(Update, start_playback.run_if(in_state(AppState::Menu)));
+                }
+                Err(err) => error!("Could not load replay {}: {}", path.display(), err),
+            }
+        }
+    }
+}
+
+fn start_recording(mut commands: Commands, starting_loadout: Res<StartingLoadout>) {
+    commands.insert_resource(Recording(Replay {
+        loadout: starting_loadout.0.clone(),
+        ..Default::default()
+    }));
+}
+
+fn record_frame(
+    mut recording: ResMut<Recording>,
+    query: Query<&Engine, With<IsPlayer>>,
+) {
+    if let Ok(engine) = query.get_single() {
+        recording.0.frames.push(ReplayFrame { target: engine.target });
+    }
+}
+
+/// Save the run so far, the recording is finished afterwards so it is only saved once
+#[cfg(not(target_arch = "wasm32"))]
+fn save_recording(mut commands: Commands, mut recording: ResMut<Recording>, rng: Res<GameRng>) {
+    recording.0.version = REPLAY_VERSION;
+    recording.0.seed = rng.seed;
+    let path = PathBuf::from(REPLAY_FILE);
+    match recording.0.save(&path) {
+        Ok(()) => info!("Saved replay to {}", path.display()),
+        Err(err) => warn!("Could not save replay to {}: {}", path.display(), err),
+    }
+    commands.remove_resource::<Recording>();
+}
+
+// Closing the window mid-run keeps the replay
+#[cfg(not(target_arch = "wasm32"))]
+fn save_recording_on_exit(world: &mut World) {
+    if world.resource::<Events<bevy::app::AppExit>>().iter_current_update_events().next().is_some() {
+        if let Err(err) = world.run_system_cached(save_recording) {
+            warn!("Could not save replay: {}", err);
+        }
+    }
+}
+
+fn start_playback(mut app_state: ResMut<NextState<AppState>>) {
+    app_state.set(AppState::InGame);
+}
+
+fn playback_frame(mut playback: ResMut<Playback>, mut query: Query<&mut Engine, With<IsPlayer>>) {
+    let Some(frame) = playback.replay.frames.get(playback.frame).copied() else { return; };
+    playback.frame += 1;
+    for mut engine in &mut query {
+        engine.target = frame.target;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn playback_selection(
+    mut playback: ResMut<Playback>,
+    mut rng: ResMut<GameRng>,
+    player_level: Res<PlayerLevel>,
+    upgrades: Res<PlayerUpgrades>,
+    mut banished: ResMut<BanishedUpgrades>,
+    mut charges: ResMut<SelectionCharges>,
+    mut offered: ResMut<OfferedUpgrades>,
+    mut upgrade_event: EventWriter<UpgradeEvent>,
+    mut skip_event: EventWriter<SkipEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // The screen closes by itself once nothing is left to offer
+    while !offered.0.is_empty() {
+        let Some(choice) = playback.replay.selections.get(playback.selection).copied() else { break; };
+        playback.selection += 1;
+        charges.spend(choice);
+        match choice {
+            SelectionChoice::Pick(upgrade) => {
+                upgrade_event.send(upgrade);
+                break;
+            }
+            SelectionChoice::Skip => {
+                skip_event.send(SkipEvent);
+                break;
+            }
+            SelectionChoice::Banish(upgrade) => {
+                // Replace the card so the rng advances as it did when recorded
+                banish_option(&mut *rng, &upgrades, &mut banished, &mut offered.0, upgrade);
+            }
+            SelectionChoice::Reroll => {
+                offered.0 = roll_options(&mut *rng, &player_level, &upgrades, &banished);
+            }
+        }
+    }
+    next_state.set(GameState::Running);
+}
+
+fn finish_playback(mut commands: Commands) {
+    commands.remove_resource::<Playback>();
+}
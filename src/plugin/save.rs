@@ -0,0 +1,461 @@
+use std::time::Duration;
+
+use bevy::{app::AppExit, prelude::*};
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{component::*, resource::*, system::loot_bundle, util::*, setup_new_game, setup_player, AppState, GameState};
+
+use super::{
+    spawn_enemy, ArchetypeName, EnemyArchetypes, EnemyArchetypesHandle, FinalBoss,
+    BanishedUpgrades, ObjectShape, Playback, PlayerUpgrades, Recording, SelectionCharges, SpaceObject, Spawning, SPAWN_INTERVAL, TurretDefinitions, TurretDefinitionsHandle, UpgradeEvent, AI,
+};
+
+/// Bump whenever the save format changes, older saves are ignored
+pub const SAVE_VERSION: u32 = 1;
+
+const SAVE_FILE: &str = "savegame.ron";
+
+/// Everything needed to put the player back into an unfinished run
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub seed: u64,
+    pub game_time: f32,
+    pub points: u32,
+    pub player_level: u32,
+    pub upgrades: Vec<(UpgradeEvent, u8)>,
+    pub spawning: Option<SavedSpawning>,
+    pub player: SavedPlayer,
+    pub enemies: Vec<SavedEnemy>,
+    pub loot: Vec<SavedLoot>,
+    pub space_objects: Vec<SavedSpaceObject>,
+    #[serde(default)]
+    pub stats: RunStats,
+    #[serde(default)]
+    pub banished: Vec<UpgradeEvent>,
+    #[serde(default)]
+    pub charges: SelectionCharges,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedSpawning {
+    pub max: u32,
+    pub elapsed: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedBody {
+    pub position: Vec2,
+    pub rotation: Quat,
+    pub velocity: Vec2,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedHealth {
+    pub health: i32,
+    pub shield: i32,
+    pub max_health: i32,
+    pub max_shield: i32,
+    /// Lengths of the recharge timers, shortened by upgrades
+    pub shield_recharge_cooldown: f32,
+    pub shield_recharge_timer: f32,
+    /// Progress through the recharge timers
+    #[serde(default)]
+    pub shield_recharge_cooldown_elapsed: f32,
+    #[serde(default)]
+    pub shield_recharge_timer_elapsed: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedPlayer {
+    pub body: SavedBody,
+    pub health: SavedHealth,
+    pub power: f32,
+    pub max_speed: f32,
+    pub cargo: u32,
+    pub cargo_bonus_chance: f32,
+    pub magnet_range: f32,
+    pub magnet_strength: f32,
+    pub turrets: Vec<SavedTurret>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedTurret {
+    pub class: TurretClass,
+    pub level: u8,
+    pub fire_rate: f32,
+    pub damage: i32,
+    pub crit_chance: f32,
+    pub shots: u8,
+    pub size: f32,
+    pub range: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedEnemy {
+    pub archetype: String,
+    pub final_boss: bool,
+    pub body: SavedBody,
+    pub health: SavedHealth,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedLoot {
+    pub body: SavedBody,
+    pub points: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedSpaceObject {
+    pub body: SavedBody,
+    pub radius: f32,
+    pub spin: f32,
+    pub health: SavedHealth,
+    /// Older saves without a shape get a new random one
+    #[serde(default)]
+    pub shape: Vec<f32>,
+}
+
+impl SaveGame {
+    pub fn exists() -> bool {
+        Storage::exists(SAVE_FILE)
+    }
+
+    pub fn load() -> Result<SaveGame, String> {
+        let contents = Storage::read(SAVE_FILE)?;
+        let save: SaveGame = ron::from_str(&contents).map_err(|err| err.to_string())?;
+        if save.version != SAVE_VERSION {
+            return Err(format!("save version {} is not supported, expected {}", save.version, SAVE_VERSION));
+        }
+        Ok(save)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|err| err.to_string())?;
+        Storage::write(SAVE_FILE, &contents)
+    }
+
+    pub fn delete() {
+        if let Err(err) = Storage::remove(SAVE_FILE) {
+            warn!("Could not delete save {}: {}", SAVE_FILE, err);
+        }
+    }
+}
+
+/// Present while entering a run that continues from a save
+#[derive(Resource)]
+pub struct ResumeRun(pub SaveGame);
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame),
+                resume_run
+                    .after(setup_new_game)
+                    .after(setup_player)
+                    .run_if(resource_exists::<ResumeRun>),
+            )
+            // Starting or restarting a run abandons the saved one
+            .add_systems(OnEnter(AppState::InGame), delete_save.run_if(not(resource_exists::<ResumeRun>)).run_if(not(resource_exists::<Playback>)))
+            .add_systems(OnExit(AppState::InGame), finish_resume.run_if(resource_exists::<ResumeRun>))
+            .add_systems(OnEnter(GameState::Paused), save_run)
+            .add_systems(Last, save_run_on_exit.run_if(in_state(AppState::InGame)))
+            // A finished run can't be continued
+            .add_systems(OnEnter(GameState::GameOver), delete_save);
+    }
+}
+
+impl SavedBody {
+    fn from_parts(transform: &Transform, physics: &Physics) -> Self {
+        SavedBody {
+            position: transform.translation.truncate(),
+            rotation: transform.rotation,
+            velocity: physics.velocity,
+        }
+    }
+
+    fn apply(&self, transform: &mut Transform, physics: &mut Physics) {
+        transform.translation = self.position.extend(transform.translation.z);
+        transform.rotation = self.rotation;
+        physics.velocity = self.velocity;
+    }
+}
+
+impl SavedHealth {
+    fn from_health(health: &Health) -> Self {
+        SavedHealth {
+            health: health.health,
+            shield: health.shield,
+            max_health: health.max_health,
+            max_shield: health.max_shield,
+            shield_recharge_cooldown: health.shield_recharge_cooldown.duration().as_secs_f32(),
+            shield_recharge_timer: health.shield_recharge_timer.duration().as_secs_f32(),
+            shield_recharge_cooldown_elapsed: health.shield_recharge_cooldown.elapsed_secs(),
+            shield_recharge_timer_elapsed: health.shield_recharge_timer.elapsed_secs(),
+        }
+    }
+
+    fn apply(&self, health: &mut Health) {
+        health.health = self.health;
+        health.shield = self.shield;
+        health.max_health = self.max_health;
+        health.max_shield = self.max_shield;
+        health.shield_recharge_cooldown.set_duration(Duration::from_secs_f32(self.shield_recharge_cooldown));
+        health.shield_recharge_timer.set_duration(Duration::from_secs_f32(self.shield_recharge_timer));
+        health.shield_recharge_cooldown.set_elapsed(Duration::from_secs_f32(self.shield_recharge_cooldown_elapsed));
+        health.shield_recharge_timer.set_elapsed(Duration::from_secs_f32(self.shield_recharge_timer_elapsed));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_run(
+    rng: Res<GameRng>,
+    game_time: Res<GameTime>,
+    points: Res<Points>,
+    player_level: Res<PlayerLevel>,
+    upgrades: Res<PlayerUpgrades>,
+    banished: Res<BanishedUpgrades>,
+    charges: Res<SelectionCharges>,
+    stats: Res<RunStats>,
+    spawning: Option<Res<Spawning>>,
+    player_query: Query<(&Transform, &Physics, &Health, &Engine, &Cargo, &Magnet, Option<&Children>), With<IsPlayer>>,
+    turret_query: Query<(&TurretClass, &TurretLevel, &FireRate, &DoesDamage, &MultiShot, &EffectSize, &Range)>,
+    enemy_query: Query<(&ArchetypeName, &Transform, &Physics, &Health, Has<FinalBoss>), With<AI>>,
+    loot_query: Query<(&Transform, &Physics, Option<&WorthPoints>), With<IsLoot>>,
+    object_query: Query<(&Transform, &Physics, &Collider, &Rotator, &Health, &ObjectShape), With<SpaceObject>>,
+) {
+    let Ok((transform, physics, health, engine, cargo, magnet, children)) = player_query.get_single() else { return; };
+
+    let turrets = children
+        .into_iter()
+        .flat_map(|children| children.iter())
+        .filter_map(|child| turret_query.get(*child).ok())
+        .map(|(class, level, fire_rate, damage, shots, size, range)| SavedTurret {
+            class: *class,
+            level: level.0,
+            fire_rate: fire_rate.rate,
+            damage: damage.amount,
+            crit_chance: damage.crit_chance,
+            shots: shots.amount,
+            size: size.0,
+            range: range.max,
+        })
+        .collect();
+
+    let save = SaveGame {
+        version: SAVE_VERSION,
+        seed: rng.seed,
+        game_time: game_time.0.elapsed_secs(),
+        points: points.value,
+        player_level: player_level.value,
+        upgrades: upgrades.0.iter().map(|(upgrade, level)| (*upgrade, *level)).collect(),
+        stats: stats.clone(),
+        banished: banished.0.iter().copied().collect(),
+        charges: *charges,
+        spawning: spawning.map(|spawning| SavedSpawning {
+            max: spawning.max,
+            elapsed: spawning.timer.elapsed_secs(),
+        }),
+        player: SavedPlayer {
+            body: SavedBody::from_parts(transform, physics),
+            health: SavedHealth::from_health(health),
+            power: engine.power,
+            max_speed: engine.max_speed,
+            cargo: cargo.amount,
+            cargo_bonus_chance: cargo.bonus_chance,
+            magnet_range: magnet.range,
+            magnet_strength: magnet.strength,
+            turrets,
+        },
+        enemies: enemy_query
+            .iter()
+            .map(|(archetype, transform, physics, health, final_boss)| SavedEnemy {
+                archetype: archetype.0.clone(),
+                final_boss,
+                body: SavedBody::from_parts(transform, physics),
+                health: SavedHealth::from_health(health),
+            })
+            .collect(),
+        loot: loot_query
+            .iter()
+            .map(|(transform, physics, worth_points)| SavedLoot {
+                body: SavedBody::from_parts(transform, physics),
+                points: worth_points.map(|worth_points| worth_points.value),
+            })
+            .collect(),
+        space_objects: object_query
+            .iter()
+            .map(|(transform, physics, collider, rotator, health, shape)| SavedSpaceObject {
+                body: SavedBody::from_parts(transform, physics),
+                radius: collider.radius,
+                spin: rotator.speed,
+                health: SavedHealth::from_health(health),
+                shape: shape.0.clone(),
+            })
+            .collect(),
+    };
+
+    match save.save() {
+        Ok(()) => info!("Saved run to {}", SAVE_FILE),
+        Err(err) => warn!("Could not save run to {}: {}", SAVE_FILE, err),
+    }
+}
+
+// Closing the window mid-run keeps the progress
+fn save_run_on_exit(world: &mut World) {
+    let exiting = world.resource::<Events<AppExit>>().iter_current_update_events().next().is_some();
+    let game_over = *world.resource::<State<GameState>>().get() == GameState::GameOver;
+    if exiting && !game_over {
+        if let Err(err) = world.run_system_cached(save_run) {
+            warn!("Could not save run: {}", err);
+        }
+    }
+}
+
+fn delete_save() {
+    SaveGame::delete();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn resume_run(
+    mut commands: Commands,
+    resume: Res<ResumeRun>,
+    fonts: Res<Fonts>,
+    // Missing when enemies are disabled
+    archetypes: Option<Res<Assets<EnemyArchetypes>>>,
+    archetypes_handle: Option<Res<EnemyArchetypesHandle>>,
+    definitions: Res<Assets<TurretDefinitions>>,
+    definitions_handle: Res<TurretDefinitionsHandle>,
+    mut rng: ResMut<GameRng>,
+    mut upgrades: ResMut<PlayerUpgrades>,
+    mut player_query: Query<(Entity, &mut Transform, &mut Physics, &mut Health, &mut Engine, &mut Cargo, &mut Magnet), With<IsPlayer>>,
+) {
+    let save = &resume.0;
+
+    // Rng state isn't saved, continue with the seed the run was started from
+    *rng = GameRng::from_seed(save.seed);
+
+    // A resumed run is no longer reproducible from its inputs
+    commands.remove_resource::<Recording>();
+
+    let mut game_time = GameTime::default();
+    game_time.0.set_elapsed(Duration::from_secs_f32(save.game_time));
+    commands.insert_resource(game_time);
+    commands.insert_resource(Points { value: save.points });
+    commands.insert_resource(PlayerLevel { value: save.player_level });
+    commands.insert_resource(save.stats.clone());
+    upgrades.0 = save.upgrades.iter().copied().collect();
+    commands.insert_resource(BanishedUpgrades(save.banished.iter().copied().collect()));
+    commands.insert_resource(save.charges);
+
+    if let Some(saved) = &save.spawning {
+        let mut timer = Timer::from_seconds(SPAWN_INTERVAL, TimerMode::Repeating);
+        timer.set_elapsed(Duration::from_secs_f32(saved.elapsed));
+        commands.insert_resource(Spawning { max: saved.max, timer });
+    }
+
+    // Player
+    if let Ok((entity, mut transform, mut physics, mut health, mut engine, mut cargo, mut magnet)) = player_query.get_single_mut() {
+        let saved = &save.player;
+        saved.body.apply(&mut transform, &mut physics);
+        saved.health.apply(&mut health);
+        engine.power = saved.power;
+        engine.max_speed = saved.max_speed;
+        cargo.amount = saved.cargo;
+        cargo.bonus_chance = saved.cargo_bonus_chance;
+        magnet.range = saved.magnet_range;
+        magnet.strength = saved.magnet_strength;
+
+        let definitions = definitions.get(&definitions_handle.0);
+        commands.entity(entity).with_children(|parent| {
+            for turret in &saved.turrets {
+                let mut bundle = definitions
+                    .and_then(|definitions| definitions.get(&turret.class))
+                    .map(|definition| definition.bundle())
+                    .unwrap_or_else(|| TurretBundle { class: turret.class, ..Default::default() });
+                bundle.level = TurretLevel(turret.level);
+                bundle.fire_rate = FireRate::from_rate_in_seconds(turret.fire_rate);
+                bundle.damage = DoesDamage { amount: turret.damage, crit_chance: turret.crit_chance };
+                bundle.shots = MultiShot { amount: turret.shots };
+                bundle.size = EffectSize(turret.size);
+                bundle.range = Range { max: turret.range };
+                parent.spawn(bundle);
+            }
+        });
+    }
+
+    // Enemies
+    let archetypes = archetypes
+        .zip(archetypes_handle)
+        .and_then(|(archetypes, handle)| archetypes.into_inner().get(&handle.0));
+    if let Some(archetypes) = archetypes {
+        for saved in &save.enemies {
+            let Some(archetype) = archetypes.get(&saved.archetype) else {
+                warn!("Saved enemy {} no longer exists", saved.archetype);
+                continue;
+            };
+            let entity = spawn_enemy(&mut commands, &fonts, archetype, saved.body.position.extend(RenderLayer::Enemy.as_z()));
+            let mut health = Health::new(archetype.health, archetype.shield);
+            saved.health.apply(&mut health);
+            commands.entity(entity).insert((
+                health,
+                Transform::from_translation(saved.body.position.extend(RenderLayer::Enemy.as_z())).with_rotation(saved.body.rotation),
+                Physics { velocity: saved.body.velocity, ..archetype.physics() },
+            ));
+            if saved.final_boss {
+                commands.entity(entity).insert(FinalBoss);
+            }
+        }
+    }
+
+    // Loot
+    for saved in &save.loot {
+        let mut loot = commands.spawn(loot_bundle(
+            &fonts,
+            Transform::from_translation(saved.body.position.extend(RenderLayer::Enemy.as_z())).with_rotation(saved.body.rotation),
+            saved.body.velocity,
+            Vec2::ZERO,
+        ));
+        if let Some(value) = saved.points {
+            loot.insert(WorthPoints { value });
+        }
+    }
+
+    // Space objects
+    for saved in &save.space_objects {
+        let mut health = Health::new(saved.health.max_health, saved.health.max_shield);
+        saved.health.apply(&mut health);
+        let shape = if saved.shape.is_empty() {
+            ObjectShape::random(&mut *rng, 10, saved.radius - 10., saved.radius + 10.)
+        } else {
+            ObjectShape(saved.shape.clone())
+        };
+        commands.spawn((
+            SpaceObject,
+            Collider { radius: saved.radius },
+            Physics { velocity: saved.body.velocity, face_velocity: false, mass: saved.radius, ..Default::default() },
+            Rotator { speed: saved.spin },
+            health,
+            Stroke::new(Colour::WHITE, 2.0),
+            ShapeBundle {
+                path: shape.path(),
+                transform: Transform::from_translation(saved.body.position.extend(RenderLayer::Background.as_z()))
+                    .with_rotation(saved.body.rotation),
+                ..default()
+            },
+            ExplodesOnDespawn {
+                size_min: 50.0,
+                size_max: 100.0,
+                ..Default::default()
+            },
+            shape,
+            DespawnWithScene,
+        ));
+    }
+}
+
+fn finish_resume(mut commands: Commands) {
+    commands.remove_resource::<ResumeRun>();
+}
@@ -1,18 +1,28 @@
 use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
 use rand::Rng;
 
-use crate::{component::TurretClass, resource::*, util::Colour, GameState};
+use crate::{component::TurretClass, input::PlayerAction, resource::*, util::Colour, GameState};
 
-use super::{PlayerUpgrades, UpgradeEvent};
+use super::{BanishedUpgrades, OfferedUpgrades, Playback, PlayerUpgrades, Recording, SelectionCharges, SelectionChoice, SkipEvent, UpgradeEvent};
 
 #[derive(Resource)]
 struct SelectionData(pub Vec<Entity>);
 
+/// Upgrade offered and its position in the row
 #[derive(Component)]
-struct SelectionButton(UpgradeEvent);
+struct SelectionButton(UpgradeEvent, usize);
+
+/// Reroll, skip or banish, spending one of its charges
+#[derive(Component)]
+struct ActionButton(SelectionChoice);
+
+const SELECT_ACTIONS: [PlayerAction; 3] = [PlayerAction::SelectFirst, PlayerAction::SelectSecond, PlayerAction::SelectThird];
 
 const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
 const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_EVOLUTION_BUTTON: Color = Color::srgb(0.3, 0.27, 0.15);
+const NORMAL_EVOLUTION_BUTTON: Color = Color::srgb(0.2, 0.17, 0.08);
 
 pub struct SelectionPlugin;
 
@@ -20,13 +30,13 @@ impl Plugin for SelectionPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(SelectionData(vec![]))
             .add_systems(OnEnter(GameState::Selection), setup_selection)
-            .add_systems(Update, menu.run_if(in_state(GameState::Selection)))
+            .add_systems(Update, (select_with_input, menu, selection_actions).chain().run_if(in_state(GameState::Selection)).run_if(not(resource_exists::<Playback>)))
             .add_systems(OnExit(GameState::Selection), cleanup);
     }
 }
 
-fn random_starting_weapon() -> TurretClass {
-    match rand::thread_rng().gen_range(0..4) {
+fn random_starting_weapon(rng: &mut impl Rng) -> TurretClass {
+    match rng.gen_range(0..4) {
         0 => TurretClass::AutoCannon,
         1 => TurretClass::BlastLaser,
         2 => TurretClass::RocketLauncher,
@@ -34,10 +44,10 @@ fn random_starting_weapon() -> TurretClass {
     }
 }
 
-fn roll_starting() -> Vec<UpgradeEvent> {
+fn roll_starting(rng: &mut impl Rng) -> Vec<UpgradeEvent> {
     let mut options: Vec<UpgradeEvent> = vec![];
     while options.len() < 3 {
-        let potential = UpgradeEvent::Weapon(random_starting_weapon());
+        let potential = UpgradeEvent::Weapon(random_starting_weapon(rng));
         if !options.contains(&potential) {
             options.push(potential);
         }
@@ -45,20 +55,37 @@ fn roll_starting() -> Vec<UpgradeEvent> {
     options
 }
 
-fn roll(upgrades: Res<PlayerUpgrades>) -> Vec<UpgradeEvent> {
-    let mut options: Vec<UpgradeEvent> = vec![];
+fn roll(rng: &mut impl Rng, upgrades: &PlayerUpgrades, banished: &BanishedUpgrades) -> Vec<UpgradeEvent> {
+    let mut options = vec![];
+    fill(rng, upgrades, banished, &mut options);
+    options
+}
+
+/// Top the options up to three, keeping those already offered. May offer fewer once upgrades run out
+fn fill(rng: &mut impl Rng, upgrades: &PlayerUpgrades, banished: &BanishedUpgrades, options: &mut Vec<UpgradeEvent>) {
+    // Evolutions are always offered once ready
+    for evolution in upgrades.available_evolutions() {
+        let option = UpgradeEvent::Evolve(evolution.evolved);
+        if options.len() < 3 && !options.contains(&option) && !banished.0.contains(&option) {
+            options.push(option);
+        }
+    }
     let mut iterations = 0;
     while options.len() < 3 {
         iterations += 1;
 
+        // Offer a heal when nothing else is left, fewer cards when that is banished too
         if iterations > 100 {
-            options.push(UpgradeEvent::Heal);
-            continue;
+            let heal = UpgradeEvent::Heal;
+            if !options.contains(&heal) && !banished.0.contains(&heal) {
+                options.push(heal);
+            }
+            break;
         }
 
-        let potential: UpgradeEvent = rand::random();
-        // No duplicates
-        if options.contains(&potential) {
+        let potential: UpgradeEvent = rng.gen();
+        // No duplicates or banished upgrades
+        if options.contains(&potential) || banished.0.contains(&potential) {
             continue;
         }
 
@@ -69,11 +96,18 @@ fn roll(upgrades: Res<PlayerUpgrades>) -> Vec<UpgradeEvent> {
             continue;
         }
 
+        // Evolved weapons have replaced their original
+        if let UpgradeEvent::Weapon(weapon) = potential {
+            if upgrades.evolved(weapon) {
+                continue;
+            }
+        }
+
         // Cannot have too many passives or weapons
         let cap_reached = match potential {
             UpgradeEvent::Weapon(_) => upgrades.reached_max_weapons(),
             UpgradeEvent::Passive(_) => upgrades.reached_max_passives(),
-            UpgradeEvent::Heal => false,
+            UpgradeEvent::Heal | UpgradeEvent::Evolve(_) => false,
         };
         // If new check we haven't had too many of given type
         if current_level == &0 && cap_reached {
@@ -82,40 +116,117 @@ fn roll(upgrades: Res<PlayerUpgrades>) -> Vec<UpgradeEvent> {
 
         options.push(potential);
     }
-    options
 }
 
+/// Banish an offered upgrade and roll a replacement in its place, the other options stay
+pub fn banish_option(
+    rng: &mut impl Rng,
+    upgrades: &PlayerUpgrades,
+    banished: &mut BanishedUpgrades,
+    options: &mut Vec<UpgradeEvent>,
+    upgrade: UpgradeEvent,
+) {
+    banished.0.insert(upgrade);
+    let Some(index) = options.iter().position(|option| *option == upgrade) else { return; };
+    options.remove(index);
+    let kept = options.len();
+    fill(rng, upgrades, banished, options);
+    if options.len() > kept {
+        let replacement = options.remove(kept);
+        options.insert(index, replacement);
+    }
+}
+
+/// Roll the upgrade options offered when reaching the given level
+pub fn roll_options(rng: &mut impl Rng, player_level: &PlayerLevel, upgrades: &PlayerUpgrades, banished: &BanishedUpgrades) -> Vec<UpgradeEvent> {
+    match player_level.value {
+        1 => roll_starting(rng),
+        _ => roll(rng, upgrades, banished),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn setup_selection(
     mut commands: Commands,
     fonts: Res<Fonts>,
     mut menu_data: ResMut<SelectionData>,
     player_level: Res<PlayerLevel>,
     upgrades: Res<PlayerUpgrades>,
+    banished: Res<BanishedUpgrades>,
+    charges: Res<SelectionCharges>,
+    mut offered: ResMut<OfferedUpgrades>,
+    mut rng: ResMut<GameRng>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
     // Roll for options
-    let options = match player_level.value {
-        1 => roll_starting(),
-        _ => roll(upgrades),
-    };
+    offered.0 = roll_options(&mut *rng, &player_level, &upgrades, &banished);
+    // Nothing left to offer
+    if offered.0.is_empty() {
+        next_state.set(GameState::Running);
+        return;
+    }
+    let root_entity = spawn_selection(&mut commands, &fonts, offered.0.clone(), &player_level, &charges);
+    menu_data.0.push(root_entity);
+}
 
-    let root_entity = commands
+fn spawn_selection(
+    commands: &mut Commands,
+    fonts: &Res<Fonts>,
+    options: Vec<UpgradeEvent>,
+    player_level: &PlayerLevel,
+    charges: &SelectionCharges,
+) -> Entity {
+    // The starting weapon has to be picked
+    let show_actions = player_level.value > 1;
+    commands
         .spawn(Node {
             width: Val::Percent(100.0),
             height: Val::Percent(100.0),
             position_type: PositionType::Absolute,
             justify_content: JustifyContent::Center,
             align_items: AlignItems::Center,
-            flex_direction: FlexDirection::Row,
-            column_gap: Val::Px(10.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(20.0),
             ..Default::default()
         })
         .with_children(|parent| {
-            for option in options {
-                button(parent, &fonts, option);
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(10.0),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for (index, option) in options.into_iter().enumerate() {
+                        parent
+                            .spawn(Node {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                row_gap: Val::Px(5.0),
+                                ..Default::default()
+                            })
+                            .with_children(|parent| {
+                                button(parent, fonts, option, index);
+                                if show_actions {
+                                    action_button(parent, fonts, SelectionChoice::Banish(option), charges);
+                                }
+                            });
+                    }
+                });
+            if show_actions {
+                parent
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(10.0),
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        action_button(parent, fonts, SelectionChoice::Reroll, charges);
+                        action_button(parent, fonts, SelectionChoice::Skip, charges);
+                    });
             }
         })
-        .id();
-    menu_data.0.push(root_entity);
+        .id()
 }
 
 fn menu(
@@ -125,19 +236,96 @@ fn menu(
         (Changed<Interaction>, With<Button>, With<SelectionButton>),
     >,
     mut upgrade_event: EventWriter<UpgradeEvent>,
+    mut recording: Option<ResMut<Recording>>,
+    mut sound_event: EventWriter<SoundEvent>,
 ) {
     for (interaction, mut color, button) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
+                if let Some(recording) = recording.as_mut() {
+                    recording.0.selections.push(SelectionChoice::Pick(button.0));
+                }
                 upgrade_event.send(button.0);
+                sound_event.send(SoundEvent::Confirm);
                 next_state.set(GameState::Running);
             }
             Interaction::Hovered => {
-                *color = HOVERED_BUTTON.into();
+                *color = match button.0 {
+                    UpgradeEvent::Evolve(_) => HOVERED_EVOLUTION_BUTTON.into(),
+                    _ => HOVERED_BUTTON.into(),
+                };
             }
             Interaction::None => {
-                *color = NORMAL_BUTTON.into();
+                *color = match button.0 {
+                    UpgradeEvent::Evolve(_) => NORMAL_EVOLUTION_BUTTON.into(),
+                    _ => NORMAL_BUTTON.into(),
+                };
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn selection_actions(
+    mut commands: Commands,
+    fonts: Res<Fonts>,
+    mut menu_data: ResMut<SelectionData>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut interaction_query: Query<(&Interaction, &mut BackgroundColor, &ActionButton), (Changed<Interaction>, With<Button>)>,
+    player_level: Res<PlayerLevel>,
+    upgrades: Res<PlayerUpgrades>,
+    mut banished: ResMut<BanishedUpgrades>,
+    mut charges: ResMut<SelectionCharges>,
+    mut offered: ResMut<OfferedUpgrades>,
+    mut rng: ResMut<GameRng>,
+    mut recording: Option<ResMut<Recording>>,
+    mut skip_event: EventWriter<SkipEvent>,
+    mut sound_event: EventWriter<SoundEvent>,
+) {
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                let choice = button.0;
+                if !charges.spend(choice) {
+                    continue;
+                }
+                if let Some(recording) = recording.as_mut() {
+                    recording.0.selections.push(choice);
+                }
+                sound_event.send(SoundEvent::Confirm);
+                if choice == SelectionChoice::Skip {
+                    skip_event.send(SkipEvent);
+                    next_state.set(GameState::Running);
+                    return;
+                }
+                match choice {
+                    // Only the banished card is replaced
+                    SelectionChoice::Banish(upgrade) => banish_option(&mut *rng, &upgrades, &mut banished, &mut offered.0, upgrade),
+                    _ => offered.0 = roll_options(&mut *rng, &player_level, &upgrades, &banished),
+                }
+                if offered.0.is_empty() {
+                    next_state.set(GameState::Running);
+                    return;
+                }
+                for entity in menu_data.0.drain(..) {
+                    commands.entity(entity).despawn_recursive();
+                }
+                let root_entity = spawn_selection(&mut commands, &fonts, offered.0.clone(), &player_level, &charges);
+                menu_data.0.push(root_entity);
+                return;
             }
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+/// Press the button picked with a key or gamepad
+fn select_with_input(action_state: Res<ActionState<PlayerAction>>, mut query: Query<(&SelectionButton, &mut Interaction)>) {
+    let Some(chosen) = SELECT_ACTIONS.iter().position(|action| action_state.just_pressed(action)) else { return; };
+    for (button, mut interaction) in &mut query {
+        if button.1 == chosen {
+            *interaction = Interaction::Pressed;
         }
     }
 }
@@ -151,16 +339,22 @@ fn cleanup(mut commands: Commands, mut menu_data: ResMut<SelectionData>) {
     menu_data.0.clear();
 }
 
-fn button(parent: &mut ChildBuilder, fonts: &Res<Fonts>, upgrade: UpgradeEvent) {
+fn button(parent: &mut ChildBuilder, fonts: &Res<Fonts>, upgrade: UpgradeEvent, index: usize) {
     let type_text = match upgrade {
         UpgradeEvent::Weapon(_) => format!("Weapon"),
         UpgradeEvent::Passive(_) => format!("Passive"),
         UpgradeEvent::Heal => format!("Consumable"),
+        UpgradeEvent::Evolve(_) => format!("Evolution"),
     };
     let type_color = match upgrade {
         UpgradeEvent::Weapon(_) => Colour::RED,
         UpgradeEvent::Passive(_) => Colour::SHIELD,
         UpgradeEvent::Heal => Colour::GREEN,
+        UpgradeEvent::Evolve(_) => Colour::YELLOW,
+    };
+    let (background, border) = match upgrade {
+        UpgradeEvent::Evolve(_) => (NORMAL_EVOLUTION_BUTTON, Colour::YELLOW),
+        _ => (NORMAL_BUTTON, Color::NONE),
     };
     parent
         .spawn((
@@ -173,14 +367,16 @@ fn button(parent: &mut ChildBuilder, fonts: &Res<Fonts>, upgrade: UpgradeEvent)
                 flex_direction: FlexDirection::Column,
                 padding: UiRect::all(Val::Px(10.0)),
                 column_gap: Val::Px(10.0),
+                border: UiRect::all(Val::Px(2.0)),
                 ..default()
             },
-            BackgroundColor(NORMAL_BUTTON),
-            SelectionButton(upgrade),
+            BackgroundColor(background),
+            BorderColor(border),
+            SelectionButton(upgrade, index),
         ))
         .with_children(|parent| {
             parent.spawn((
-                Text(type_text),
+                Text(format!("{} {}", index + 1, type_text)),
                 TextFont {  font_size: 14.0, font: fonts.primary.clone(), ..Default::default() },
                 TextColor(type_color),
                 TextLayout::new_with_justify(JustifyText::Center),
@@ -219,3 +415,86 @@ fn button(parent: &mut ChildBuilder, fonts: &Res<Fonts>, upgrade: UpgradeEvent)
             );
         });
 }
+
+fn action_button(parent: &mut ChildBuilder, fonts: &Res<Fonts>, choice: SelectionChoice, charges: &SelectionCharges) {
+    let label = match choice {
+        SelectionChoice::Reroll => "Reroll",
+        SelectionChoice::Skip => "Skip",
+        SelectionChoice::Banish(_) => "Banish",
+        SelectionChoice::Pick(_) => "Pick",
+    };
+    let remaining = charges.remaining(choice);
+    let text_color = if remaining > 0 { Color::srgb(0.9, 0.9, 0.9) } else { Color::srgba(0.8, 0.8, 0.8, 0.3) };
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(140.0),
+                height: Val::Px(32.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(NORMAL_BUTTON),
+            ActionButton(choice),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text(format!("{} ({})", label, remaining)),
+                TextFont { font_size: 14.0, font: fonts.primary.clone(), ..Default::default() },
+                TextColor(text_color),
+            ));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn banish_rolls(rng: &mut impl Rng, count: usize) -> BanishedUpgrades {
+        BanishedUpgrades((0..count).map(|_| rng.gen::<UpgradeEvent>()).collect())
+    }
+
+    #[test]
+    fn roll_never_offers_banished_or_duplicates() {
+        let upgrades = PlayerUpgrades::default();
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let banished = banish_rolls(&mut rng, 4);
+            let options = roll(&mut rng, &upgrades, &banished);
+            assert_eq!(options.len(), 3);
+            for (index, option) in options.iter().enumerate() {
+                assert!(!banished.0.contains(option));
+                assert!(!options[index + 1..].contains(option));
+            }
+        }
+    }
+
+    #[test]
+    fn roll_falls_back_to_a_single_heal() {
+        let upgrades = PlayerUpgrades::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut banished = banish_rolls(&mut rng, 10_000);
+        assert!(roll(&mut rng, &upgrades, &banished) == vec![UpgradeEvent::Heal]);
+
+        banished.0.insert(UpgradeEvent::Heal);
+        assert!(roll(&mut rng, &upgrades, &banished).is_empty());
+    }
+
+    #[test]
+    fn banish_replaces_only_the_banished_option() {
+        let upgrades = PlayerUpgrades::default();
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut banished = BanishedUpgrades::default();
+            let mut options = roll(&mut rng, &upgrades, &banished);
+            let before = options.clone();
+            banish_option(&mut rng, &upgrades, &mut banished, &mut options, before[1]);
+            assert_eq!(options.len(), 3);
+            assert!(options[0] == before[0] && options[2] == before[2]);
+            assert!(!before.contains(&options[1]));
+            assert!(banished.0.contains(&before[1]));
+        }
+    }
+}
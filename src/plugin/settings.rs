@@ -0,0 +1,172 @@
+use bevy::{core_pipeline::bloom::Bloom, prelude::*, window::{MonitorSelection, PrimaryWindow, WindowMode}};
+use serde::{Deserialize, Serialize};
+
+use crate::{component::MainCamera, util::Storage, AppState};
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+pub const BLOOM_INTENSITY: f32 = 0.15;
+
+/// Player preferences, kept between sessions
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    pub shake_scale: f32,
+    pub bloom: bool,
+    pub fullscreen: bool,
+    pub default_zoom: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            music_volume: 1.0,
+            shake_scale: 1.0,
+            bloom: true,
+            fullscreen: false,
+            default_zoom: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Setting {
+    MasterVolume,
+    SfxVolume,
+    MusicVolume,
+    ShakeScale,
+    Bloom,
+    Fullscreen,
+    DefaultZoom,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 7] = [
+        Setting::MasterVolume,
+        Setting::SfxVolume,
+        Setting::MusicVolume,
+        Setting::ShakeScale,
+        Setting::Bloom,
+        Setting::Fullscreen,
+        Setting::DefaultZoom,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Setting::MasterVolume => "Master Volume",
+            Setting::SfxVolume => "Effects Volume",
+            Setting::MusicVolume => "Music Volume",
+            Setting::ShakeScale => "Screen Shake",
+            Setting::Bloom => "Bloom",
+            Setting::Fullscreen => "Fullscreen",
+            Setting::DefaultZoom => "Default Zoom",
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Settings {
+        if !Storage::exists(SETTINGS_FILE) {
+            return Settings::default();
+        }
+        match Storage::read(SETTINGS_FILE).and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string())) {
+            Ok(settings) => settings,
+            Err(err) => {
+                warn!("Could not load settings from {}: {}", SETTINGS_FILE, err);
+                Settings::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|err| err.to_string())?;
+        Storage::write(SETTINGS_FILE, &contents)
+    }
+
+    /// Step a setting up or down, toggles flip either way
+    pub fn adjust(&mut self, setting: Setting, direction: f32) {
+        match setting {
+            Setting::MasterVolume => self.master_volume = (self.master_volume + direction * 0.1).clamp(0.0, 1.0),
+            Setting::SfxVolume => self.sfx_volume = (self.sfx_volume + direction * 0.1).clamp(0.0, 1.0),
+            Setting::MusicVolume => self.music_volume = (self.music_volume + direction * 0.1).clamp(0.0, 1.0),
+            Setting::ShakeScale => self.shake_scale = (self.shake_scale + direction * 0.25).clamp(0.0, 2.0),
+            Setting::Bloom => self.bloom = !self.bloom,
+            Setting::Fullscreen => self.fullscreen = !self.fullscreen,
+            Setting::DefaultZoom => self.default_zoom = (self.default_zoom + direction * 0.25).clamp(1.0, 3.0),
+        }
+    }
+
+    pub fn display(&self, setting: Setting) -> String {
+        let on_off = |value: bool| if value { "On" } else { "Off" }.to_string();
+        match setting {
+            Setting::MasterVolume => format!("{:.0}%", self.master_volume * 100.0),
+            Setting::SfxVolume => format!("{:.0}%", self.sfx_volume * 100.0),
+            Setting::MusicVolume => format!("{:.0}%", self.music_volume * 100.0),
+            Setting::ShakeScale => format!("{:.0}%", self.shake_scale * 100.0),
+            Setting::Bloom => on_off(self.bloom),
+            Setting::Fullscreen => on_off(self.fullscreen),
+            Setting::DefaultZoom => format!("{:.2}x", self.default_zoom),
+        }
+    }
+}
+
+/// Whether the settings screen is shown over the current menu
+#[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum SettingsState {
+    #[default]
+    Closed,
+    Open,
+    /// Rebinding controls, opened from the settings screen
+    Controls,
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .init_state::<SettingsState>()
+            .add_systems(OnEnter(AppState::Setup), load_settings)
+            .add_systems(Update, (apply_display_settings, apply_zoom_setting).run_if(resource_changed::<Settings>))
+            .add_systems(OnEnter(AppState::InGame), reset_zoom);
+    }
+}
+
+fn load_settings(mut settings: ResMut<Settings>) {
+    *settings = Settings::load();
+}
+
+fn apply_display_settings(
+    settings: Res<Settings>,
+    mut bloom_query: Query<&mut Bloom, With<MainCamera>>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for mut bloom in &mut bloom_query {
+        bloom.intensity = if settings.bloom { BLOOM_INTENSITY } else { 0.0 };
+    }
+    for mut window in &mut window_query {
+        let mode = if settings.fullscreen { WindowMode::BorderlessFullscreen(MonitorSelection::Current) } else { WindowMode::Windowed };
+        if window.mode != mode {
+            window.mode = mode;
+        }
+    }
+}
+
+fn apply_zoom_setting(settings: Res<Settings>, mut last_default: Local<Option<f32>>, query: Query<&mut OrthographicProjection, With<MainCamera>>) {
+    // Only move the camera when the default itself changes
+    if *last_default != Some(settings.default_zoom) {
+        *last_default = Some(settings.default_zoom);
+        reset_zoom(settings, query);
+    }
+}
+
+fn reset_zoom(settings: Res<Settings>, mut query: Query<&mut OrthographicProjection, With<MainCamera>>) {
+    for mut projection in &mut query {
+        projection.scale = settings.default_zoom;
+    }
+}
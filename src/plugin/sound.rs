@@ -0,0 +1,146 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{component::TurretClass, resource::SoundEvent};
+
+use super::{Settings, TurretFireEvent};
+
+/// Most copies of one sound playing at once
+const MAX_VOICES_PER_SOUND: usize = 4;
+
+/// Most sound effects playing at once
+const MAX_VOICES: usize = 24;
+
+/// Volume slider a sound follows
+#[derive(Clone, Copy)]
+pub enum SoundCategory {
+    Effects,
+    Music,
+}
+
+impl SoundCategory {
+    pub fn volume(&self, settings: &Settings) -> f32 {
+        settings.master_volume
+            * match self {
+                SoundCategory::Effects => settings.sfx_volume,
+                SoundCategory::Music => settings.music_volume,
+            }
+    }
+}
+
+#[derive(Resource)]
+struct Sounds {
+    turrets: HashMap<TurretClass, Handle<AudioSource>>,
+    events: HashMap<SoundEvent, Handle<AudioSource>>,
+}
+
+/// Marks a playing sound effect so voices can be counted
+#[derive(Component)]
+struct SoundEffect;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_sounds)
+            .add_systems(Update, play_sounds);
+    }
+}
+
+fn turret_sound(class: TurretClass) -> &'static str {
+    match class {
+        TurretClass::AutoCannon | TurretClass::VulcanCannon => "sounds/auto_cannon.wav",
+        TurretClass::BlastLaser | TurretClass::StormLaser => "sounds/blast_laser.wav",
+        TurretClass::RocketLauncher => "sounds/rocket_launcher.wav",
+        TurretClass::MineLauncher => "sounds/mine_launcher.wav",
+        TurretClass::ShrapnelCannon => "sounds/shrapnel_cannon.wav",
+        TurretClass::ChainLaser => "sounds/chain_laser.wav",
+        TurretClass::PierceLaser => "sounds/pierce_laser.wav",
+        TurretClass::Emp => "sounds/emp.wav",
+    }
+}
+
+fn event_sound(event: SoundEvent) -> &'static str {
+    match event {
+        SoundEvent::ShieldHit => "sounds/shield_hit.wav",
+        SoundEvent::ArmourHit => "sounds/armour_hit.wav",
+        SoundEvent::EnemyDeath => "sounds/enemy_death.wav",
+        SoundEvent::LootPickup => "sounds/loot_pickup.wav",
+        SoundEvent::LevelUp => "sounds/level_up.wav",
+        SoundEvent::Confirm => "sounds/confirm.wav",
+    }
+}
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let turrets = [
+        TurretClass::AutoCannon,
+        TurretClass::BlastLaser,
+        TurretClass::RocketLauncher,
+        TurretClass::MineLauncher,
+        TurretClass::ShrapnelCannon,
+        TurretClass::ChainLaser,
+        TurretClass::PierceLaser,
+        TurretClass::Emp,
+        TurretClass::VulcanCannon,
+        TurretClass::StormLaser,
+    ];
+    let events = [
+        SoundEvent::ShieldHit,
+        SoundEvent::ArmourHit,
+        SoundEvent::EnemyDeath,
+        SoundEvent::LootPickup,
+        SoundEvent::LevelUp,
+        SoundEvent::Confirm,
+    ];
+    commands.insert_resource(Sounds {
+        turrets: turrets.into_iter().map(|class| (class, asset_server.load(turret_sound(class)))).collect(),
+        events: events.into_iter().map(|event| (event, asset_server.load(event_sound(event)))).collect(),
+    });
+}
+
+fn play_sounds(
+    mut commands: Commands,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+    mut fire_events: EventReader<TurretFireEvent>,
+    mut sound_events: EventReader<SoundEvent>,
+    voices: Query<&AudioPlayer, With<SoundEffect>>,
+) {
+    let volume = SoundCategory::Effects.volume(&settings);
+    if volume <= 0.0 {
+        // Still consume the events so they don't all play once unmuted
+        fire_events.clear();
+        sound_events.clear();
+        return;
+    }
+    let requested = fire_events
+        .read()
+        .filter_map(|ev| sounds.turrets.get(&ev.class))
+        .chain(sound_events.read().filter_map(|ev| sounds.events.get(ev)));
+
+    let mut playing: HashMap<AssetId<AudioSource>, usize> = HashMap::new();
+    for voice in &voices {
+        *playing.entry(voice.0.id()).or_default() += 1;
+    }
+    let mut total = voices.iter().len();
+    let mut started = HashSet::new();
+
+    for handle in requested {
+        // A volley or a burst of hits in one frame is heard once
+        if total >= MAX_VOICES || !started.insert(handle.id()) {
+            continue;
+        }
+        let count = playing.entry(handle.id()).or_default();
+        if *count >= MAX_VOICES_PER_SOUND {
+            continue;
+        }
+        *count += 1;
+        total += 1;
+        commands.spawn((
+            AudioPlayer::new(handle.clone()),
+            PlaybackSettings::DESPAWN.with_volume(Volume::new(volume)),
+            SoundEffect,
+        ));
+    }
+}
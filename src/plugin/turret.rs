@@ -6,12 +6,16 @@ mod shrapnel_cannon;
 mod chain_laser;
 mod pierce_laser;
 mod emp;
+mod vulcan_cannon;
+mod storm_laser;
+mod definition;
 
 use bevy::prelude::*;
 
-use crate::AppState;
 use crate::component::*;
-use crate::game_not_paused;
+use crate::resource::PendingAssets;
+use crate::GameplaySet;
+use crate::util::{RonAssetLoader, SpatialIndex, SpatialLayer};
 
 use self::blast_laser::*;
 use self::rocket_launcher::*;
@@ -21,38 +25,48 @@ use self::shrapnel_cannon::*;
 use self::chain_laser::*;
 use self::pierce_laser::*;
 use self::emp::*;
+use self::vulcan_cannon::*;
+use self::storm_laser::*;
+pub use self::definition::*;
 
 pub struct TurretPlugin;
 
 impl Plugin for TurretPlugin {
     fn build(&self, app: &mut App) {
         app
+            .init_asset::<TurretDefinitions>()
+            .register_asset_loader(RonAssetLoader::<TurretDefinitions>::default())
+            .add_systems(Startup, load_turret_definitions)
             .add_event::<TurretFireEvent>()
-            .add_systems(Update,
+            .add_systems(FixedUpdate,
                 (
                     turret_targetting_system,
                     turret_fire_system,
+                    (
+                        fire_blast_laser,
+                        fire_rocket_launcher,
+                        fire_mine_launcher,
+                        fire_auto_cannon,
+                        fire_shrapnel_cannon,
+                        fire_chain_laser,
+                        fire_pierce_laser,
+                        fire_emp,
+                        fire_vulcan_cannon,
+                        fire_storm_laser,
+                    ),
                 )
-                    .distributive_run_if(game_not_paused)
-                    .distributive_run_if(in_state(AppState::InGame)),
-            )
-            .add_systems(Update,
-                (
-
-                    fire_blast_laser,
-                    fire_rocket_launcher,
-                    fire_mine_launcher,
-                    fire_auto_cannon,
-                    fire_shrapnel_cannon,
-                    fire_chain_laser,
-                    fire_pierce_laser,
-                    fire_emp,
-                )
-                    .distributive_run_if(in_state(AppState::InGame)),
+                    .chain()
+                    .in_set(GameplaySet::Ai),
             );
     }
 }
 
+fn load_turret_definitions(mut commands: Commands, asset_server: Res<AssetServer>, mut pending: ResMut<PendingAssets>) {
+    let handle = asset_server.load::<TurretDefinitions>("turrets.ron");
+    pending.0.push(handle.clone().untyped());
+    commands.insert_resource(TurretDefinitionsHandle(handle));
+}
+
 #[derive(Event)]
 pub struct TurretFireEvent {
     pub class: TurretClass,
@@ -75,6 +89,7 @@ pub fn get_closest_target(
 }
 
 fn turret_targetting_system(
+    index: Res<SpatialIndex>,
     mut query: Query<(&mut Targets, &Parent, &Range)>,
     target_query: Query<(Entity, &Transform, &Targettable)>,
     parent_query: Query<(&Transform, Entity, &WillTarget)>,
@@ -92,8 +107,11 @@ fn turret_targetting_system(
                 }
             } else {
                 // Look for a target
-                let mut potentials_without_parent: Vec<(Entity, &Transform, &Targettable)> = target_query
+                let layers: Vec<SpatialLayer> = parent_will_target.0.iter().map(SpatialLayer::from_allegiance).collect();
+                let mut potentials_without_parent: Vec<(Entity, &Transform, &Targettable)> = index
+                    .query_circle(&layers, parent_transform.translation.truncate(), range.max)
                     .iter()
+                    .filter_map(|potential| target_query.get(potential.entity).ok())
                     .filter(|a| a.0 != parent_entity && parent_will_target.0.contains(&a.2.0))
                     .filter(|a| a.1.translation.truncate().distance(parent_transform.translation.truncate()) <= range.max)
                     .collect();
@@ -106,10 +124,15 @@ fn turret_targetting_system(
 fn turret_fire_system(
     mut commands: Commands,
     time: Res<Time>,
-    mut query: Query<(&mut FireRate, &TurretClass, &mut Targets, Entity)>,
+    mut query: Query<(&mut FireRate, &TurretClass, &mut Targets, Entity, &Parent)>,
+    status_query: Query<&StatusEffects>,
     mut fire_event: EventWriter<TurretFireEvent>,
 ) {
-    for (mut fire_rate, class, mut targets, entity) in &mut query {
+    for (mut fire_rate, class, mut targets, entity, parent) in &mut query {
+        // Stunned ships hold their fire where it is
+        if status_query.get(parent.get()).is_ok_and(|effects| effects.is_stunned()) {
+            continue;
+        }
         if let Some(target) = targets.target {
             // Check target still exists and if not clear it
             match commands.get_entity(target) {
@@ -1,23 +1,24 @@
 use bevy::prelude::*;
 
-use crate::{component::*, util::*, resource::Fonts};
+use crate::{component::*, util::*, resource::{Fonts, GameRng}};
 
 use super::TurretFireEvent;
 
 pub fn fire_auto_cannon(
     mut commands: Commands,
     mut fire_event: EventReader<TurretFireEvent>,
-    turret_query: Query<(&Parent, &Targets, &DoesDamage, &EffectColour)>,
+    turret_query: Query<(&Parent, &Targets, &DoesDamage, &EffectColour, &AppliesStatus)>,
     parent_query: Query<&Transform>,
     target_query: Query<&Transform>,
     fonts: Res<Fonts>,
+    mut rng: ResMut<GameRng>,
 ) {
     for ev in fire_event.read() {
         match ev.class {
             TurretClass::AutoCannon => {
 
                 // Get Turret Info
-                let Ok((parent, targets, damage, colour)) = turret_query.get(ev.turret) else { continue; };
+                let Ok((parent, targets, damage, colour, status)) = turret_query.get(ev.turret) else { continue; };
 
                 // Get Target
                 let Some(target) = targets.target else { continue; };
@@ -46,7 +47,9 @@ pub fn fire_auto_cannon(
                     },
                     Collider { radius: 5.0 },
                     Owner(parent.get()),
-                    DirectDamage(damage.roll()),
+                    FiredBy(ev.class),
+                    DirectDamage(damage.roll(&mut *rng)),
+                    *status,
                     DespawnWithScene,
                 ));
 
@@ -1,24 +1,25 @@
 use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
 
-use crate::{component::*, util::*, resource::TakeDamageEvent};
+use crate::{component::*, util::*, resource::{GameRng, TakeDamageEvent}};
 
 use super::TurretFireEvent;
 
 pub fn fire_blast_laser(
     mut commands: Commands,
     mut fire_event: EventReader<TurretFireEvent>,
-    turret_query: Query<(&Parent, &Targets, &DoesDamage, &EffectColour)>,
+    turret_query: Query<(&Parent, &Targets, &DoesDamage, &EffectColour, &AppliesStatus)>,
     parent_query: Query<&Transform>,
     target_query: Query<&Transform>,
     mut take_damage_event: EventWriter<TakeDamageEvent>,
+    mut rng: ResMut<GameRng>,
 ) {
     for ev in fire_event.read() {
         match ev.class {
             TurretClass::BlastLaser => {
 
                 // Get Turret Info
-                let Ok((parent, targets, damage, colour)) = turret_query.get(ev.turret) else { continue; };
+                let Ok((parent, targets, damage, colour, status)) = turret_query.get(ev.turret) else { continue; };
 
                 // Get Target
                 let Some(target) = targets.target else { continue; };
@@ -42,11 +43,12 @@ pub fn fire_blast_laser(
                     },
                     Stroke::new(colour.0, 1.0),
                     Owner(parent.get()),
+                    FiredBy(ev.class),
                     DespawnWithScene,
                 ));
 
                 // Immediate hit
-                take_damage_event.send(TakeDamageEvent { entity: target, damage: damage.roll() });
+                take_damage_event.send(TakeDamageEvent { entity: target, damage: damage.roll(&mut *rng), source: DamageSource::weapon(parent.get(), TurretClass::BlastLaser).with_status(status), origin: Some(origin) });
 
             },
             _ => (),
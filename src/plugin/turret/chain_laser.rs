@@ -1,12 +1,12 @@
 use bevy::{prelude::*, ecs::query::QueryEntityError};
 use bevy_prototype_lyon::prelude::*;
 
-use crate::{component::*, util::*, resource::TakeDamageEvent};
+use crate::{component::*, util::*, resource::{GameRng, TakeDamageEvent}};
 
 use super::{TurretFireEvent, get_closest_target};
 
 
-fn spawn_link<'a>(commands: &mut Commands, take_damage_event: &mut EventWriter<TakeDamageEvent>, target_query: &'a Query<&Transform>, origin: Vec2, target: Entity, damage: &DoesDamage, jump: u8, colour: &EffectColour, owner: Entity) -> Result<Vec2, QueryEntityError<'a>> {
+fn spawn_link<'a>(commands: &mut Commands, take_damage_event: &mut EventWriter<TakeDamageEvent>, rng: &mut GameRng, target_query: &'a Query<&Transform>, origin: Vec2, target: Entity, damage: &DoesDamage, jump: u8, colour: &EffectColour, owner: Entity, status: &AppliesStatus) -> Result<Vec2, QueryEntityError<'a>> {
     // Get Target Info
     let target_transform = target_query.get(target)?;
     let target_position = target_transform.translation.truncate();
@@ -21,28 +21,30 @@ fn spawn_link<'a>(commands: &mut Commands, take_damage_event: &mut EventWriter<T
         },
         Stroke::new(colour.0, 2.0),
         Owner(owner),
+        FiredBy(TurretClass::ChainLaser),
         DespawnWithScene,
     ));
     // Immediate hit
-    take_damage_event.send(TakeDamageEvent { entity: target, damage: damage.roll() });
+    take_damage_event.send(TakeDamageEvent { entity: target, damage: damage.roll(rng), source: DamageSource::weapon(owner, TurretClass::ChainLaser).with_status(status), origin: Some(origin) });
     Ok(target_position)
 }
 
 pub fn fire_chain_laser(
     mut commands: Commands,
     mut fire_event: EventReader<TurretFireEvent>,
-    turret_query: Query<(&Parent, &Targets, &DoesDamage, &MultiShot, &EffectColour)>,
+    turret_query: Query<(&Parent, &Targets, &DoesDamage, &MultiShot, &EffectColour, &AppliesStatus)>,
     parent_query: Query<(&Transform, &WillTarget)>,
     target_query: Query<&Transform>,
     potential_query: Query<(Entity, &Transform, &Targettable), (With<Targettable>, With<Transform>)>,
     mut take_damage_event: EventWriter<TakeDamageEvent>,
+    mut rng: ResMut<GameRng>,
 ) {
     for ev in fire_event.read() {
         match ev.class {
             TurretClass::ChainLaser => {
 
                 // Get Turret Info
-                let Ok((parent, targets, damage, shots, colour)) = turret_query.get(ev.turret) else { continue; };
+                let Ok((parent, targets, damage, shots, colour, status)) = turret_query.get(ev.turret) else { continue; };
 
                 // Get Target
                 let Some(target) = targets.target else { continue; };
@@ -70,7 +72,7 @@ pub fn fire_chain_laser(
                     // Remove target from potentials list so no repeats
                     potential_targets.retain(|potential| potential.0 != target);
                     
-                    let result = spawn_link(&mut commands, &mut take_damage_event, &target_query, previous_position, target, damage, num_jumps, colour, parent.get());
+                    let result = spawn_link(&mut commands, &mut take_damage_event, &mut rng, &target_query, previous_position, target, damage, num_jumps, colour, parent.get(), status);
 
                     match result {
                         Ok(pos) => {
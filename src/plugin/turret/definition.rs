@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{component::*, util::Colour};
+
+/// Base stats and level tables of every player weapon, loaded from `turrets.ron`
+#[derive(Asset, TypePath, Deserialize)]
+pub struct TurretDefinitions {
+    pub turrets: Vec<TurretDefinition>,
+}
+
+impl TurretDefinitions {
+    pub fn get(&self, class: &TurretClass) -> Option<&TurretDefinition> {
+        self.turrets.iter().find(|turret| turret.class == *class)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TurretDefinition {
+    pub class: TurretClass,
+    pub fire_rate: f32,
+    pub damage: i32,
+    #[serde(default)]
+    pub range: Option<f32>,
+    #[serde(default)]
+    pub shots: Option<u8>,
+    #[serde(default)]
+    pub size: Option<f32>,
+    /// Name of a `Colour` constant
+    pub colour: String,
+    #[serde(default)]
+    pub status: Option<StatusEffect>,
+    /// Changes applied on reaching level 2, 3 and so on
+    #[serde(default)]
+    pub levels: Vec<Vec<StatDelta>>,
+}
+
+impl TurretDefinition {
+    pub fn bundle(&self) -> TurretBundle {
+        TurretBundle {
+            class: self.class,
+            range: self.range.map(|max| Range { max }).unwrap_or_default(),
+            fire_rate: FireRate::from_rate_in_seconds(self.fire_rate),
+            damage: DoesDamage::from_amount(self.damage),
+            shots: self.shots.map(|amount| MultiShot { amount }).unwrap_or_default(),
+            size: EffectSize(self.size.unwrap_or_default()),
+            colour: Colour::named(&self.colour).map(EffectColour).unwrap_or_default(),
+            status: AppliesStatus(self.status),
+            ..Default::default()
+        }
+    }
+
+    pub fn deltas_for_level(&self, level: u8) -> &[StatDelta] {
+        level
+            .checked_sub(2)
+            .and_then(|index| self.levels.get(index as usize))
+            .map(|deltas| deltas.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum StatDelta {
+    /// Multiplies shots per second
+    FireRateScale(f32),
+    Damage(i32),
+    Shots(u8),
+    Size(f32),
+    /// Multiplies effect size
+    SizeScale(f32),
+    Range(f32),
+}
+
+impl StatDelta {
+    pub fn apply(&self, fire_rate: &mut FireRate, damage: &mut DoesDamage, shots: &mut MultiShot, size: &mut EffectSize, range: &mut Range) {
+        match self {
+            StatDelta::FireRateScale(scale) => {
+                let new_rate = fire_rate.rate * scale;
+                fire_rate.set_rate_in_seconds(new_rate);
+            }
+            StatDelta::Damage(amount) => damage.amount += amount,
+            StatDelta::Shots(amount) => shots.amount += amount,
+            StatDelta::Size(amount) => size.0 += amount,
+            StatDelta::SizeScale(scale) => size.0 *= scale,
+            StatDelta::Range(amount) => range.max += amount,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct TurretDefinitionsHandle(pub Handle<TurretDefinitions>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definitions() -> TurretDefinitions {
+        ron::from_str(include_str!("../../../assets/turrets.ron")).expect("turrets.ron should parse")
+    }
+
+    #[test]
+    fn turrets_ron_matches_original_stats() {
+        let definitions = definitions();
+        // Class, shots per second, damage, shots, size, colour
+        let original = [
+            (TurretClass::AutoCannon, 2.0, 2, None, None, "PLAYER"),
+            (TurretClass::BlastLaser, 1.5, 1, None, None, "PINK"),
+            (TurretClass::RocketLauncher, 0.5, 5, None, None, "YELLOW"),
+            (TurretClass::MineLauncher, 0.9, 6, Some(3), Some(40.0), "PLAYER"),
+            (TurretClass::ShrapnelCannon, 0.25, 2, Some(16), None, "PLAYER"),
+            (TurretClass::ChainLaser, 0.4, 1, Some(3), None, "GREEN"),
+            (TurretClass::PierceLaser, 0.15, 8, None, Some(1.0), "YELLOW"),
+            (TurretClass::Emp, 0.7, 3, None, Some(80.0), "SHIELD"),
+        ];
+        for (class, fire_rate, damage, shots, size, colour) in original {
+            let definition = definitions.get(&class).unwrap_or_else(|| panic!("missing {}", class));
+            assert_eq!(definition.fire_rate, fire_rate, "{} fire rate", class);
+            assert_eq!(definition.damage, damage, "{} damage", class);
+            assert_eq!(definition.shots, shots, "{} shots", class);
+            assert_eq!(definition.size, size, "{} size", class);
+            assert_eq!(definition.colour, colour, "{} colour", class);
+            // Levels 2 to 8
+            assert_eq!(definition.levels.len(), 7, "{} levels", class);
+        }
+    }
+
+    #[test]
+    fn turrets_ron_level_upgrades_match_original() {
+        let definitions = definitions();
+        for definition in &definitions.turrets {
+            assert!(Colour::named(&definition.colour).is_some(), "{} colour {}", definition.class, definition.colour);
+            for level in 2..=8 {
+                let deltas = definition.deltas_for_level(level);
+                let expected = match definition.class {
+                    TurretClass::AutoCannon | TurretClass::BlastLaser => matches!(deltas, [StatDelta::FireRateScale(scale)] if *scale == 2.0),
+                    TurretClass::RocketLauncher | TurretClass::ChainLaser => matches!(deltas, [StatDelta::Shots(1)]),
+                    TurretClass::ShrapnelCannon => matches!(deltas, [StatDelta::Damage(1)]),
+                    TurretClass::MineLauncher => matches!(deltas, [StatDelta::SizeScale(scale)] if *scale == 1.5),
+                    TurretClass::PierceLaser => matches!(deltas, [StatDelta::Size(amount)] if *amount == 2.0),
+                    TurretClass::Emp => matches!(deltas, [StatDelta::Size(amount)] if *amount == 20.0),
+                    // Evolutions arrive at max level
+                    TurretClass::VulcanCannon | TurretClass::StormLaser => deltas.is_empty(),
+                };
+                assert!(expected, "{} level {}", definition.class, level);
+            }
+        }
+    }
+
+    #[test]
+    fn deltas_outside_the_table_are_empty() {
+        let definitions = definitions();
+        let auto_cannon = definitions.get(&TurretClass::AutoCannon).unwrap();
+        assert!(auto_cannon.deltas_for_level(0).is_empty());
+        assert!(auto_cannon.deltas_for_level(1).is_empty());
+        assert_eq!(auto_cannon.deltas_for_level(2).len(), 1);
+        assert_eq!(auto_cannon.deltas_for_level(8).len(), 1);
+        assert!(auto_cannon.deltas_for_level(9).is_empty());
+        assert!(auto_cannon.deltas_for_level(u8::MAX).is_empty());
+    }
+}
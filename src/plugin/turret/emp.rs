@@ -1,22 +1,23 @@
 use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
 
-use crate::{component::*, util::*};
+use crate::{component::*, util::*, resource::GameRng};
 
 use super::TurretFireEvent;
 
 pub fn fire_emp(
     mut commands: Commands,
     mut fire_event: EventReader<TurretFireEvent>,
-    turret_query: Query<(&Parent, &DoesDamage, &EffectSize, &EffectColour)>,
+    turret_query: Query<(&Parent, &DoesDamage, &EffectSize, &EffectColour, &AppliesStatus)>,
     parent_query: Query<&Transform>,
+    mut rng: ResMut<GameRng>,
 ) {
     for ev in fire_event.read() {
         match ev.class {
             TurretClass::Emp => {
 
                 // Get Turret Info
-                let Ok((parent, damage, size, colour)) = turret_query.get(ev.turret) else { continue; };
+                let Ok((parent, damage, size, colour, status)) = turret_query.get(ev.turret) else { continue; };
 
                 // Get Parent Info
                 let Ok(parent_transform) = parent_query.get(parent.get()) else { continue; };
@@ -50,8 +51,10 @@ pub fn fire_emp(
                     Transform::from_translation(parent_transform.translation),
                     Collider { radius: 0.0 },
                     ExpandingCollider { final_radius: size.0 },
-                    DirectDamage(damage.roll()),
+                    DirectDamage(damage.roll(&mut *rng)),
                     Owner(parent.get()),
+                    FiredBy(ev.class),
+                    *status,
                 ));
 
             },
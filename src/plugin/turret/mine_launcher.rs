@@ -1,22 +1,23 @@
 use bevy::prelude::*;
 
-use crate::{component::*, util::*, resource::Fonts};
+use crate::{component::*, util::*, resource::{Fonts, GameRng}};
 
 use super::TurretFireEvent;
 
 pub fn fire_mine_launcher(
     mut commands: Commands,
     mut fire_event: EventReader<TurretFireEvent>,
-    turret_query: Query<(&Parent, &DoesDamage, &EffectSize, &EffectColour, &MultiShot)>,
+    turret_query: Query<(&Parent, &DoesDamage, &EffectSize, &EffectColour, &MultiShot, &AppliesStatus)>,
     parent_query: Query<&Transform>,
     fonts: Res<Fonts>,
+    mut rng: ResMut<GameRng>,
 ) {
     for ev in fire_event.read() {
         match ev.class {
             TurretClass::MineLauncher => {
 
                 // Get Turret Info
-                let Ok((parent, damage, size, colour, shots)) = turret_query.get(ev.turret) else { continue; };
+                let Ok((parent, damage, size, colour, shots, status)) = turret_query.get(ev.turret) else { continue; };
 
                 // Get Parent Info
                 let Ok(parent_transform) = parent_query.get(parent.get()) else { continue; };
@@ -33,6 +34,7 @@ pub fn fire_mine_launcher(
                     Health::new(1, 0),
                     Collider { radius: size.0 },
                     Owner(parent.get()),
+                    FiredBy(ev.class),
                     ExplodesOnDespawn {
                         amount_min: shots.amount as u32,
                         amount_max: shots.amount as u32,
@@ -42,7 +44,8 @@ pub fn fire_mine_launcher(
                         spread: size.0,
                         ..Default::default()
                     },
-                    AoeDamage { damage: damage.roll(), range: size.0 },
+                    AoeDamage { damage: damage.roll(&mut *rng), range: size.0 },
+                    *status,
                     DespawnWithScene,
                 ));
 
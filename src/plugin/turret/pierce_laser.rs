@@ -1,25 +1,26 @@
 use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
 
-use crate::{component::*, util::*, resource::TakeDamageEvent};
+use crate::{component::*, util::*, resource::{GameRng, TakeDamageEvent}};
 
 use super::TurretFireEvent;
 
 pub fn fire_pierce_laser(
     mut commands: Commands,
     mut fire_event: EventReader<TurretFireEvent>,
-    turret_query: Query<(&Parent, &Targets, &DoesDamage, &EffectSize, &EffectColour)>,
+    turret_query: Query<(&Parent, &Targets, &DoesDamage, &EffectSize, &EffectColour, &AppliesStatus)>,
     parent_query: Query<(&Transform, &WillTarget)>,
     target_query: Query<&Transform>,
     potential_query: Query<(Entity, &Transform, &Targettable, &Collider)>,
     mut take_damage_event: EventWriter<TakeDamageEvent>,
+    mut rng: ResMut<GameRng>,
 ) {
     for ev in fire_event.read() {
         match ev.class {
             TurretClass::PierceLaser => {
 
                 // Get Turret Info
-                let Ok((parent, targets, damage, size, colour)) = turret_query.get(ev.turret) else { continue; };
+                let Ok((parent, targets, damage, size, colour, status)) = turret_query.get(ev.turret) else { continue; };
 
                 // Get Target
                 let Some(target) = targets.target else { continue; };
@@ -45,6 +46,7 @@ pub fn fire_pierce_laser(
                     },
                     Stroke::new(colour.0, size.0),
                     Owner(parent.get()),
+                    FiredBy(ev.class),
                     DespawnWithScene,
                 ));
 
@@ -53,7 +55,7 @@ pub fn fire_pierce_laser(
                     .iter()
                     .filter(|a| a.0 != parent.get() && parent_will_target.0.contains(&a.2.0))
                     .filter(|a| Math::distance_from_point_to_line(a.1.translation.truncate(), origin, end) <= a.3.radius + size.0)
-                    .map(|hit| TakeDamageEvent { entity: hit.0, damage: damage.roll() });
+                    .map(|hit| TakeDamageEvent { entity: hit.0, damage: damage.roll(&mut *rng), source: DamageSource::weapon(parent.get(), TurretClass::PierceLaser).with_status(status), origin: Some(origin) });
                 take_damage_event.send_batch(events);
 
             },
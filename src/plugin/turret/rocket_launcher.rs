@@ -2,23 +2,24 @@ use std::f32::consts::PI;
 
 use bevy::prelude::*;
 
-use crate::{component::*, util::*, resource::Fonts};
+use crate::{component::*, util::*, resource::{Fonts, GameRng}};
 
 use super::TurretFireEvent;
 
 pub fn fire_rocket_launcher(
     mut commands: Commands,
     mut fire_event: EventReader<TurretFireEvent>,
-    turret_query: Query<(&Parent, &Targets, &DoesDamage, &MultiShot, &EffectColour)>,
+    turret_query: Query<(&Parent, &Targets, &DoesDamage, &MultiShot, &EffectColour, &AppliesStatus)>,
     parent_query: Query<&Transform>,
     fonts: Res<Fonts>,
+    mut rng: ResMut<GameRng>,
 ) {
     for ev in fire_event.read() {
         match ev.class {
             TurretClass::RocketLauncher => {
 
                 // Get Turret Info
-                let Ok((parent, targets, damage, shots, colour)) = turret_query.get(ev.turret) else { continue; };
+                let Ok((parent, targets, damage, shots, colour, status)) = turret_query.get(ev.turret) else { continue; };
 
                 // Get Target
                 let Some(target) = targets.target else { continue; };
@@ -40,18 +41,20 @@ pub fn fire_rocket_launcher(
                             rotation: Quat::from_rotation_z(PI / 2.0),
                         },
                         Physics {
-                            velocity: Math::random_2d_unit_vector() * 100.0,
+                            velocity: Math::random_2d_unit_vector(&mut *rng) * 100.0,
                             ..Default::default()
                         },
                         Engine::new_with_steering(40.0, 10.0, 0.5),
                         Seeker(target),
                         Collider { radius: 5.0 },
                         Owner(parent.get()),
+                        FiredBy(ev.class),
                         ExplodesOnDespawn {
                             colour: colour.0,
                             ..Default::default()
                         },
-                        AoeDamage { damage: damage.roll(), range: 40.0 },
+                        AoeDamage { damage: damage.roll(&mut *rng), range: 40.0 },
+                        *status,
                         DespawnWithScene,
                     ));
                 }
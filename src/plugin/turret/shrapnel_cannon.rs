@@ -3,24 +3,25 @@ use rand::Rng;
 
 use bevy::prelude::*;
 
-use crate::{component::*, util::*, resource::Fonts};
+use crate::{component::*, util::*, resource::{Fonts, GameRng}};
 
 use super::TurretFireEvent;
 
 pub fn fire_shrapnel_cannon(
     mut commands: Commands,
     mut fire_event: EventReader<TurretFireEvent>,
-    turret_query: Query<(&Parent, &Targets, &DoesDamage, &MultiShot, &EffectColour)>,
+    turret_query: Query<(&Parent, &Targets, &DoesDamage, &MultiShot, &EffectColour, &AppliesStatus)>,
     parent_query: Query<&Transform>,
     target_query: Query<&Transform>,
     fonts: Res<Fonts>,
+    mut rng: ResMut<GameRng>,
 ) {
     for ev in fire_event.read() {
         match ev.class {
             TurretClass::ShrapnelCannon => {
 
                 // Get Turret Info
-                let Ok((parent, targets, damage, shots, colour)) = turret_query.get(ev.turret) else { continue; };
+                let Ok((parent, targets, damage, shots, colour, status)) = turret_query.get(ev.turret) else { continue; };
 
                 // Get Target
                 let Some(target) = targets.target else { continue; };
@@ -40,7 +41,6 @@ pub fn fire_shrapnel_cannon(
                 let destination = target_transform.translation.truncate();
                 let direction = (destination - origin).normalize();
 
-                let mut rng: rand::rngs::ThreadRng = rand::thread_rng();
                 for _ in 0..shots.amount {
                     let random_angle = rng.gen_range(-SPREAD / 2.0..SPREAD / 2.0);
                     let spread_direction = Vec2::from_angle(random_angle).rotate(direction);
@@ -58,7 +58,9 @@ pub fn fire_shrapnel_cannon(
                         },
                         Collider { radius: 5.0 },
                         Owner(parent.get()),
-                        DirectDamage(damage.roll()),
+                        FiredBy(ev.class),
+                        DirectDamage(damage.roll(&mut *rng)),
+                        *status,
                         DespawnWithScene,
                     ));
                 }
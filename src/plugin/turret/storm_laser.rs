@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::{component::*, util::*, resource::{GameRng, TakeDamageEvent}};
+
+use super::TurretFireEvent;
+
+pub fn fire_storm_laser(
+    mut commands: Commands,
+    mut fire_event: EventReader<TurretFireEvent>,
+    turret_query: Query<(&Parent, &DoesDamage, &MultiShot, &Range, &EffectColour, &AppliesStatus)>,
+    parent_query: Query<(&Transform, &WillTarget)>,
+    target_query: Query<(&Transform, &Targettable)>,
+    index: Res<SpatialIndex>,
+    mut take_damage_event: EventWriter<TakeDamageEvent>,
+    mut rng: ResMut<GameRng>,
+) {
+    for ev in fire_event.read() {
+        match ev.class {
+            TurretClass::StormLaser => {
+
+                // Get Turret Info
+                let Ok((parent, damage, shots, range, colour, status)) = turret_query.get(ev.turret) else { continue; };
+
+                // Get Parent Info
+                let Ok((parent_transform, parent_will_target)) = parent_query.get(parent.get()) else { continue; };
+
+                // Closest targets in range
+                let origin = parent_transform.translation.truncate();
+                let layers: Vec<SpatialLayer> = parent_will_target.0.iter().map(SpatialLayer::from_allegiance).collect();
+                let mut targets: Vec<(Entity, Vec2)> = index
+                    .query_circle(&layers, origin, range.max)
+                    .iter()
+                    .filter(|potential| potential.entity != parent.get())
+                    .filter_map(|potential| target_query.get(potential.entity).ok().map(|target| (potential.entity, target)))
+                    .filter(|(_, (_, targettable))| parent_will_target.0.contains(&targettable.0))
+                    .map(|(entity, (transform, _))| (entity, transform.translation.truncate()))
+                    .filter(|(_, position)| position.distance(origin) <= range.max)
+                    .collect();
+                targets.sort_by(|a, b| a.1.distance(origin).total_cmp(&b.1.distance(origin)));
+
+                for (target, target_pos) in targets.into_iter().take(shots.amount as usize) {
+                    // Spawn graphic
+                    commands.spawn((
+                        Bullet::new(0.1),
+                        LaserRender,
+                        ShapeBundle {
+                            path: GeometryBuilder::build_as(&shapes::Line(origin, target_pos)),
+                            transform: Transform::from_xyz(0., 0., RenderLayer::Bullet.as_z()),
+                            ..default()
+                        },
+                        Stroke::new(colour.0, 1.0),
+                        Owner(parent.get()),
+                        FiredBy(ev.class),
+                        DespawnWithScene,
+                    ));
+
+                    // Immediate hit
+                    take_damage_event.send(TakeDamageEvent { entity: target, damage: damage.roll(&mut *rng), source: DamageSource::weapon(parent.get(), TurretClass::StormLaser).with_status(status), origin: Some(origin) });
+                }
+            },
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::f32::consts::PI;
+use rand::Rng;
+
+use bevy::prelude::*;
+
+use crate::{component::*, util::*, resource::{Fonts, GameRng}};
+
+use super::TurretFireEvent;
+
+pub fn fire_vulcan_cannon(
+    mut commands: Commands,
+    mut fire_event: EventReader<TurretFireEvent>,
+    turret_query: Query<(&Parent, &Targets, &DoesDamage, &MultiShot, &EffectColour, &AppliesStatus)>,
+    parent_query: Query<&Transform>,
+    target_query: Query<&Transform>,
+    fonts: Res<Fonts>,
+    mut rng: ResMut<GameRng>,
+) {
+    for ev in fire_event.read() {
+        match ev.class {
+            TurretClass::VulcanCannon => {
+
+                // Get Turret Info
+                let Ok((parent, targets, damage, shots, colour, status)) = turret_query.get(ev.turret) else { continue; };
+
+                // Get Target
+                let Some(target) = targets.target else { continue; };
+
+                // Get Target Info
+                let Ok(target_transform) = target_query.get(target) else { continue; };
+
+                // Get Parent Info
+                let Ok(parent_transform) = parent_query.get(parent.get()) else { continue; };
+
+                // Spawn bullets that keep going through whatever they hit
+                const SPREAD: f32 = PI / 16.0;
+
+                let bullet_speed = 1200.0;
+                let origin = parent_transform.translation.truncate();
+                let destination = target_transform.translation.truncate();
+                let direction = (destination - origin).normalize();
+
+                for _ in 0..shots.amount {
+                    let random_angle = rng.gen_range(-SPREAD / 2.0..SPREAD / 2.0);
+                    let spread_direction = Vec2::from_angle(random_angle).rotate(direction);
+                    commands.spawn((
+                        Bullet { despawn_on_hit: false, ..Bullet::new(1.2) },
+                        GlyphBundle::new("-", colour.0, 16.0, fonts.primary.clone()),
+                        Transform {
+                            translation: origin.extend(RenderLayer::Bullet.as_z()),
+                            ..Default::default()
+                        },
+                        Physics {
+                            velocity: spread_direction * bullet_speed,
+                            ..Default::default()
+                        },
+                        Collider { radius: 5.0 },
+                        Owner(parent.get()),
+                        FiredBy(ev.class),
+                        DirectDamage(damage.roll(&mut *rng)),
+                        *status,
+                        DespawnWithScene,
+                    ));
+                }
+            },
+            _ => (),
+        }
+    }
+}
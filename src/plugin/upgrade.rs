@@ -1,16 +1,19 @@
-use bevy::{prelude::*, utils::HashMap};
+use bevy::{prelude::*, utils::{HashMap, HashSet}};
 use std::{fmt::Display, time::Duration};
 
 use crate::{
     component::*,
-    AppState,
+    resource::Points,
+    GameplaySet,
 };
+use super::{TurretDefinition, TurretDefinitions, TurretDefinitionsHandle};
 use rand::{
     distributions::{Distribution, Standard},
     Rng,
 };
+use serde::{Deserialize, Serialize};
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct PlayerUpgrades(pub HashMap<UpgradeEvent, u8>);
 
 impl PlayerUpgrades {
@@ -37,6 +40,24 @@ impl PlayerUpgrades {
             >= 6
     }
 
+    /// Evolutions whose weapon is at max level and whose passive has been picked
+    pub fn available_evolutions(&self) -> Vec<&'static Evolution> {
+        EVOLUTIONS
+            .iter()
+            .filter(|evolution| {
+                self.0.get(&UpgradeEvent::Weapon(evolution.weapon)).is_some_and(|level| *level >= Self::max_allowed_level())
+                    && self.0.get(&UpgradeEvent::Passive(evolution.passive)).is_some_and(|level| *level > 0)
+            })
+            .collect()
+    }
+
+    /// Weapon was given up for its evolution so can't be picked again
+    pub fn evolved(&self, weapon: TurretClass) -> bool {
+        EVOLUTIONS
+            .iter()
+            .any(|evolution| evolution.weapon == weapon && self.0.contains_key(&UpgradeEvent::Weapon(evolution.evolved)))
+    }
+
     pub fn reached_max_weapons(&self) -> bool {
         self.0
             .iter()
@@ -49,18 +70,122 @@ impl PlayerUpgrades {
     }
 }
 
-#[derive(Event, Copy, Clone, Eq, Hash, PartialEq)]
+/// A max level weapon and a passive that together turn it into a stronger weapon
+pub struct Evolution {
+    pub weapon: TurretClass,
+    pub passive: Passive,
+    pub evolved: TurretClass,
+}
+
+pub static EVOLUTIONS: [Evolution; 2] = [
+    Evolution { weapon: TurretClass::AutoCannon, passive: Passive::FireRate, evolved: TurretClass::VulcanCannon },
+    Evolution { weapon: TurretClass::BlastLaser, passive: Passive::Crit, evolved: TurretClass::StormLaser },
+];
+
+impl Evolution {
+    pub fn of(evolved: TurretClass) -> Option<&'static Evolution> {
+        EVOLUTIONS.iter().find(|evolution| evolution.evolved == evolved)
+    }
+}
+
+/// Health restored by skipping a level up, when there is any to restore
+pub const SKIP_HEAL: i32 = 20;
+
+/// Points given by skipping a level up at full health
+pub const SKIP_POINTS: u32 = 25;
+
+/// Upgrades banished from the level up screen, never offered again this run
+#[derive(Resource, Default)]
+pub struct BanishedUpgrades(pub HashSet<UpgradeEvent>);
+
+/// Upgrades on offer at the current level up
+#[derive(Resource, Default)]
+pub struct OfferedUpgrades(pub Vec<UpgradeEvent>);
+
+/// Rerolls, skips and banishes left to use on the level up screen
+#[derive(Resource, Clone, Copy, Serialize, Deserialize)]
+pub struct SelectionCharges {
+    pub reroll: u32,
+    pub skip: u32,
+    pub banish: u32,
+}
+
+impl Default for SelectionCharges {
+    fn default() -> Self {
+        SelectionCharges { reroll: 2, skip: 1, banish: 1 }
+    }
+}
+
+impl SelectionCharges {
+    /// Charges earned on reaching a level
+    pub fn earn(&mut self, level: u32) {
+        if level % 5 == 0 {
+            self.reroll += 1;
+        }
+        if level % 10 == 0 {
+            self.skip += 1;
+            self.banish += 1;
+        }
+    }
+
+    pub fn remaining(&self, choice: SelectionChoice) -> u32 {
+        match choice {
+            SelectionChoice::Pick(_) => 1,
+            SelectionChoice::Reroll => self.reroll,
+            SelectionChoice::Banish(_) => self.banish,
+            SelectionChoice::Skip => self.skip,
+        }
+    }
+
+    /// Use up a charge for the choice, false when there are none left. Picking is free
+    pub fn spend(&mut self, choice: SelectionChoice) -> bool {
+        let charges = match choice {
+            SelectionChoice::Pick(_) => return true,
+            SelectionChoice::Reroll => &mut self.reroll,
+            SelectionChoice::Banish(_) => &mut self.banish,
+            SelectionChoice::Skip => &mut self.skip,
+        };
+        if *charges == 0 {
+            return false;
+        }
+        *charges -= 1;
+        true
+    }
+}
+
+/// Something done on the level up screen, replays repeat these in order
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SelectionChoice {
+    Pick(UpgradeEvent),
+    /// Roll new options
+    Reroll,
+    /// Never offer the upgrade again and roll a new option in its place
+    Banish(UpgradeEvent),
+    /// Take a small heal or points instead of an upgrade
+    Skip,
+}
+
+#[derive(Event)]
+pub struct SkipEvent;
+
+/// Upgrades the player is given at the start of every run
+#[derive(Resource, Default)]
+pub struct StartingLoadout(pub Vec<UpgradeEvent>);
+
+#[derive(Event, Copy, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub enum UpgradeEvent {
     Weapon(TurretClass),
     Passive(Passive),
     Heal,
+    /// Replace a weapon with its evolved class
+    Evolve(TurretClass),
 }
 
 impl Distribution<UpgradeEvent> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> UpgradeEvent {
         match rng.gen_range(0..2) {
-            0 => UpgradeEvent::Weapon(rand::random()),
-            _ => UpgradeEvent::Passive(rand::random()),
+            0 => UpgradeEvent::Weapon(rng.gen()),
+            _ => UpgradeEvent::Passive(rng.gen()),
         }
     }
 }
@@ -71,21 +196,25 @@ impl Display for UpgradeEvent {
             UpgradeEvent::Weapon(weapon) => write!(f, "{}", weapon),
             UpgradeEvent::Passive(passive) => write!(f, "{}", passive),
             UpgradeEvent::Heal => write!(f, "Heal"),
+            UpgradeEvent::Evolve(weapon) => write!(f, "{}", weapon),
         }
     }
 }
 
 impl UpgradeEvent {
     pub fn describe(&self) -> String {
-        match self {
+        let description = match self {
             UpgradeEvent::Weapon(TurretClass::AutoCannon) => "Rapidly fires bullets towards the target",
             UpgradeEvent::Weapon(TurretClass::BlastLaser) => "Always hits. Deals low damage",
             UpgradeEvent::Weapon(TurretClass::ChainLaser) => "Shoots a laser that jumps to nearby enemies",
-            UpgradeEvent::Weapon(TurretClass::Emp) => "Creates a shockwave around you that deals damage to enemies",
+            UpgradeEvent::Weapon(TurretClass::Emp) => "Creates a shockwave around you that damages and briefly stuns enemies",
             UpgradeEvent::Weapon(TurretClass::MineLauncher) => "Drops mines that explode when enemies are in close proximity",
             UpgradeEvent::Weapon(TurretClass::PierceLaser) => "Shoots a heavy damaging laser that pierces through enemies",
             UpgradeEvent::Weapon(TurretClass::RocketLauncher) => "Shoots a seeking missile that explodes on impact",
             UpgradeEvent::Weapon(TurretClass::ShrapnelCannon) => "Shoots a spray of bullets in a cone towards the target",
+            UpgradeEvent::Weapon(TurretClass::VulcanCannon) | UpgradeEvent::Evolve(TurretClass::VulcanCannon) => "Sprays bullets that punch through every enemy in their path",
+            UpgradeEvent::Weapon(TurretClass::StormLaser) | UpgradeEvent::Evolve(TurretClass::StormLaser) => "Strikes several enemies in range at once",
+            UpgradeEvent::Evolve(_) => "Evolves a weapon",
             UpgradeEvent::Passive(Passive::Armor) => "Increase armor by 25",
             UpgradeEvent::Passive(Passive::Crit) => "Increase chance to deal double damage by 12.5%",
             UpgradeEvent::Passive(Passive::Experience) => "Increase chance to triple experience by 10%",
@@ -94,11 +223,19 @@ impl UpgradeEvent {
             UpgradeEvent::Passive(Passive::ShieldRecharge) => "Decrease shield hit and regeneration cooldown",
             UpgradeEvent::Passive(Passive::Speed) => "Increase engine power and max speed",
             UpgradeEvent::Heal => "Restore 50 armor or shields",
-        }.to_string()
+        };
+        match self {
+            UpgradeEvent::Weapon(class) => format!("{}. {} damage", description, class.damage_kind()),
+            UpgradeEvent::Evolve(class) => match Evolution::of(*class) {
+                Some(evolution) => format!("Replaces {}. {}", evolution.weapon, description),
+                None => description.to_string(),
+            },
+            _ => description.to_string(),
+        }
     }
 }
 
-#[derive(Copy, Clone, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub enum Passive {
     Speed,
     Magnet,
@@ -141,9 +278,14 @@ pub struct UpgradePlugin;
 
 impl Plugin for UpgradePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(PlayerUpgrades(HashMap::new()))
+        app.init_resource::<PlayerUpgrades>()
+            .init_resource::<BanishedUpgrades>()
+            .init_resource::<OfferedUpgrades>()
+            .init_resource::<SelectionCharges>()
             .add_event::<UpgradeEvent>()
-            .add_systems(Update,
+            .add_event::<SkipEvent>()
+            // Picked upgrades are applied at the start of the next simulation step
+            .add_systems(FixedUpdate,
                 (
                     record_upgrade,
                     upgrade_weapon_event,
@@ -153,8 +295,9 @@ impl Plugin for UpgradePlugin {
                     upgrade_fire_rate_events,
                     upgrade_experience_event,
                     upgrade_heal_event,
+                    skip_event,
                 )
-                    .distributive_run_if(in_state(AppState::InGame)),
+                    .in_set(GameplaySet::Input),
             );
     }
 }
@@ -166,6 +309,13 @@ fn record_upgrade(
     for ev in upgrade_event.read() {
         match ev {
             UpgradeEvent::Heal => (), // No need to record this
+            UpgradeEvent::Evolve(evolved) => {
+                // The evolved weapon takes the place of the old one
+                if let Some(evolution) = Evolution::of(*evolved) {
+                    player_upgrades.0.remove(&UpgradeEvent::Weapon(evolution.weapon));
+                }
+                player_upgrades.0.insert(UpgradeEvent::Weapon(*evolved), PlayerUpgrades::max_allowed_level());
+            }
             _ => {
                 let level = player_upgrades.0.entry(*ev).or_insert(0);
                 *level += 1;
@@ -176,25 +326,28 @@ fn record_upgrade(
 
 fn upgrade_weapon_event(
     upgrades: Res<PlayerUpgrades>,
+    definitions: Res<Assets<TurretDefinitions>>,
+    definitions_handle: Res<TurretDefinitionsHandle>,
     mut upgrade_event: EventReader<UpgradeEvent>,
     mut commands: Commands,
     player_query: Query<(Entity, Option<&Children>), With<IsPlayer>>,
-    turret_query: Query<&TurretClass>,
-    mut existing_auto_cannon: Query<&mut FireRate>,
-    mut existing_rocket_launcher: Query<&mut MultiShot>,
-    mut existing_shrapnel_cannon: Query<&mut DoesDamage>,
-    mut existing_mine_launcher: Query<&mut EffectSize>,
+    mut turret_query: Query<(&TurretClass, &mut TurretLevel, &mut FireRate, &mut DoesDamage, &mut MultiShot, &mut EffectSize, &mut Range)>,
 ) {
+    let Some(definitions) = definitions.get(&definitions_handle.0) else { return; };
     for ev in upgrade_event.read() {
         match ev {
             UpgradeEvent::Weapon(weapon) => {
+                let Some(definition) = definitions.get(weapon) else {
+                    warn!("No turret definition for {}", weapon);
+                    continue;
+                };
                 // Get player
                 for (player_entity, children) in &player_query {
                     // Search for existing
                     let existing = match children {
                         Some(children) => children.iter().find(|child| {
                             if let Ok(turret) = turret_query.get(**child) {
-                                return turret == weapon;
+                                return turret.0 == weapon;
                             }
                             return false;
                         }),
@@ -203,72 +356,57 @@ fn upgrade_weapon_event(
 
                     match existing {
                         Some(entity) => {
-                            // TODO split up logic into systems
-                            match weapon {
-                                TurretClass::AutoCannon => {
-                                    let mut fire_rate =
-                                        existing_auto_cannon.get_mut(*entity).unwrap();
-                                    let new_rate = fire_rate.rate * 2.0;
-                                    fire_rate.set_rate_in_seconds(new_rate);
-                                }
-                                TurretClass::BlastLaser => {
-                                    let mut fire_rate =
-                                        existing_auto_cannon.get_mut(*entity).unwrap();
-                                    let new_rate = fire_rate.rate * 2.0;
-                                    fire_rate.set_rate_in_seconds(new_rate);
-                                }
-                                TurretClass::RocketLauncher => {
-                                    let mut shots =
-                                        existing_rocket_launcher.get_mut(*entity).unwrap();
-                                    shots.amount += 1;
-                                }
-                                TurretClass::ShrapnelCannon => {
-                                    let mut damage =
-                                        existing_shrapnel_cannon.get_mut(*entity).unwrap();
-                                    damage.amount += 1;
-                                }
-                                TurretClass::MineLauncher => {
-                                    let mut size = existing_mine_launcher.get_mut(*entity).unwrap();
-                                    size.0 *= 1.5;
-                                }
-                                TurretClass::ChainLaser => {
-                                    let mut shots =
-                                        existing_rocket_launcher.get_mut(*entity).unwrap();
-                                    shots.amount += 1;
-                                }
-                                TurretClass::PierceLaser => {
-                                    let mut size = existing_mine_launcher.get_mut(*entity).unwrap();
-                                    size.0 += 2.0;
-                                }
-                                TurretClass::Emp => {
-                                    let mut size = existing_mine_launcher.get_mut(*entity).unwrap();
-                                    size.0 += 20.0;
+                            if let Ok((_, mut level, mut fire_rate, mut damage, mut shots, mut size, mut range)) = turret_query.get_mut(*entity) {
+                                level.0 += 1;
+                                for delta in definition.deltas_for_level(level.0) {
+                                    delta.apply(&mut fire_rate, &mut damage, &mut shots, &mut size, &mut range);
                                 }
                             }
                         }
-                        None => {
-                            commands.entity(player_entity).with_children(|parent| {
-                                let mut bundle = TurretBundle::from_class(weapon);
-                                
-                                // Apply existing upgrades
-                                for (upgrade, level) in upgrades.0.iter() {
-                                    match upgrade {
-                                        UpgradeEvent::Passive(passive) => apply_turret_upgrade((&mut bundle.fire_rate, &mut bundle.damage), passive, *level),
-                                        _ => (),
-                                    }
-                                }
-
-                                parent.spawn(bundle);
-                            });
-                        }
+                        None => spawn_turret(&mut commands, player_entity, definition, &upgrades, 1),
                     }
                 }
             }
+            UpgradeEvent::Evolve(evolved) => {
+                let (Some(evolution), Some(definition)) = (Evolution::of(*evolved), definitions.get(evolved)) else {
+                    warn!("No evolution or turret definition for {}", evolved);
+                    continue;
+                };
+                for (player_entity, children) in &player_query {
+                    // Swap out the turret being evolved
+                    let old = children
+                        .into_iter()
+                        .flat_map(|children| children.iter())
+                        .find(|child| turret_query.get(**child).is_ok_and(|turret| *turret.0 == evolution.weapon));
+                    if let Some(old) = old {
+                        commands.entity(*old).despawn_recursive();
+                    }
+                    // Arrives at the level it is recorded at
+                    spawn_turret(&mut commands, player_entity, definition, &upgrades, PlayerUpgrades::max_allowed_level());
+                }
+            }
             _ => (),
         }
     }
 }
 
+fn spawn_turret(commands: &mut Commands, player_entity: Entity, definition: &TurretDefinition, upgrades: &PlayerUpgrades, level: u8) {
+    commands.entity(player_entity).with_children(|parent| {
+        let mut bundle = definition.bundle();
+        bundle.level = TurretLevel(level);
+        
+        // Apply existing upgrades
+        for (upgrade, level) in upgrades.0.iter() {
+            match upgrade {
+                UpgradeEvent::Passive(passive) => apply_turret_upgrade((&mut bundle.fire_rate, &mut bundle.damage), passive, *level),
+                _ => (),
+            }
+        }
+
+        parent.spawn(bundle);
+    });
+}
+
 fn upgrade_magnet_event(
     mut upgrade_event: EventReader<UpgradeEvent>,
     mut query: Query<&mut Magnet, With<IsPlayer>>,
@@ -409,3 +547,77 @@ fn apply_turret_upgrade(turret: (&mut FireRate, &mut DoesDamage), passive: &Pass
         }
     }
 }
+
+fn skip_event(
+    mut skip_event: EventReader<SkipEvent>,
+    mut points: ResMut<Points>,
+    mut query: Query<&mut Health, With<IsPlayer>>,
+) {
+    for _ in skip_event.read() {
+        for mut health in &mut query {
+            skip_reward(&mut health, &mut points);
+        }
+    }
+}
+
+/// Heal when hurt, otherwise give points
+fn skip_reward(health: &mut Health, points: &mut Points) {
+    if health.health < health.max_health || health.shield < health.max_shield {
+        health.heal(SKIP_HEAL);
+    } else {
+        points.value += SKIP_POINTS;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charges_are_earned_every_five_and_ten_levels() {
+        let mut charges = SelectionCharges::default();
+        for level in 2..=20 {
+            charges.earn(level);
+        }
+        assert_eq!((charges.reroll, charges.skip, charges.banish), (2 + 4, 1 + 2, 1 + 2));
+    }
+
+    #[test]
+    fn remaining_matches_each_charge_and_picking_is_free() {
+        let charges = SelectionCharges { reroll: 3, skip: 2, banish: 1 };
+        assert_eq!(charges.remaining(SelectionChoice::Pick(UpgradeEvent::Heal)), 1);
+        assert_eq!(charges.remaining(SelectionChoice::Reroll), 3);
+        assert_eq!(charges.remaining(SelectionChoice::Skip), 2);
+        assert_eq!(charges.remaining(SelectionChoice::Banish(UpgradeEvent::Heal)), 1);
+    }
+
+    #[test]
+    fn spending_stops_at_zero() {
+        let mut charges = SelectionCharges { reroll: 1, skip: 0, banish: 0 };
+        assert!(charges.spend(SelectionChoice::Reroll));
+        assert!(!charges.spend(SelectionChoice::Reroll));
+        assert!(!charges.spend(SelectionChoice::Skip));
+        assert!(!charges.spend(SelectionChoice::Banish(UpgradeEvent::Heal)));
+        assert!(charges.spend(SelectionChoice::Pick(UpgradeEvent::Heal)));
+        assert_eq!(charges.reroll, 0);
+    }
+
+    #[test]
+    fn skipping_heals_when_hurt() {
+        let mut health = Health::new(100, 10);
+        health.health = 50;
+        let mut points = Points { value: 0 };
+        skip_reward(&mut health, &mut points);
+        assert_eq!(health.health, 50 + SKIP_HEAL);
+        assert_eq!(points.value, 0);
+    }
+
+    #[test]
+    fn skipping_gives_points_at_full_health() {
+        let mut health = Health::new(100, 10);
+        let mut points = Points { value: 0 };
+        skip_reward(&mut health, &mut points);
+        assert_eq!((health.health, health.shield), (100, 10));
+        assert_eq!(points.value, SKIP_POINTS);
+    }
+}
@@ -1,7 +1,9 @@
 use bevy::{prelude::*, time::Stopwatch};
-use std::fmt;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
 
-use crate::component::Damage;
+use crate::component::{Damage, DamageSource, TurretClass};
 
 #[allow(dead_code)]
 #[derive(Resource)]
@@ -24,6 +26,56 @@ impl fmt::Display for Points {
 #[derive(Resource, Default)]
 pub struct GameTime(pub Stopwatch);
 
+impl GameTime {
+    /// Goes from 1-20 difficulty in 10 minutes
+    pub fn difficulty(&self) -> u32 {
+        self.0.elapsed_secs() as u32 / 30 + 1
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub struct WeaponStats {
+    pub damage: u32,
+    pub kills: u32,
+}
+
+/// Combat record of the current run
+#[derive(Resource, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct RunStats {
+    pub weapons: HashMap<TurretClass, WeaponStats>,
+    pub shield_damage_taken: u32,
+    pub armour_damage_taken: u32,
+    /// Kills per enemy archetype
+    pub kills: HashMap<String, u32>,
+    pub loot_collected: u32,
+    /// Times `Cargo.bonus_chance` gave extra cargo
+    pub bonus_cargo: u32,
+    pub distance: f32,
+    pub peak_enemies: u32,
+}
+
+impl RunStats {
+    /// Weapons that did anything, most damage first then most kills, ties by name so the order is stable
+    pub fn weapons_by_damage(&self) -> Vec<(TurretClass, WeaponStats)> {
+        let mut weapons: Vec<_> = self.weapons.iter().map(|(class, stats)| (*class, *stats)).collect();
+        weapons.sort_by(|a, b| {
+            b.1.damage
+                .cmp(&a.1.damage)
+                .then(b.1.kills.cmp(&a.1.kills))
+                .then_with(|| a.0.to_string().cmp(&b.0.to_string()))
+        });
+        weapons
+    }
+
+    /// Archetypes killed, most kills first
+    pub fn kills_by_archetype(&self) -> Vec<(&str, u32)> {
+        let mut kills: Vec<_> = self.kills.iter().map(|(name, count)| (name.as_str(), *count)).collect();
+        kills.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        kills
+    }
+}
+
 #[derive(Resource)]
 pub struct PlayerLevel {
     pub value: u32,
@@ -35,8 +87,96 @@ impl PlayerLevel {
     }
 }
 
+/// Single source of randomness for the simulation so a run can be reproduced from its seed
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self { seed, rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::from_seed(rand::random())
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// Seed to start every run with, a new one is picked per run when not set
+#[derive(Resource, Default)]
+pub struct RunSeed(pub Option<u64>);
+
 #[derive(Event)]
 pub struct TakeDamageEvent { 
     pub entity: Entity,
     pub damage: Damage,
-}
\ No newline at end of file
+    pub source: DamageSource,
+    /// Where the hit came from, knocks the target away from it
+    pub origin: Option<Vec2>,
+}
+
+/// Something was destroyed after being hit, credited to the last hit
+#[derive(Event)]
+pub struct KillEvent {
+    pub entity: Entity,
+    pub source: DamageSource,
+    /// Enemy archetype of what was destroyed, gone from the world by the time this is read
+    pub archetype: Option<String>,
+}
+
+/// Moment in the game that has a sound effect
+#[derive(Event, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundEvent {
+    ShieldHit,
+    ArmourHit,
+    EnemyDeath,
+    LootPickup,
+    LevelUp,
+    Confirm,
+}
+
+/// Assets that must finish loading before leaving setup
+#[derive(Resource, Default)]
+pub struct PendingAssets(pub Vec<UntypedHandle>);
+
+pub fn pending_assets_loaded(pending: Res<PendingAssets>, asset_server: Res<AssetServer>) -> bool {
+    pending.0.iter().all(|handle| asset_server.is_loaded_with_dependencies(handle.id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weapons_with_equal_damage_have_a_stable_order() {
+        let mut stats = RunStats::default();
+        stats.weapons.insert(TurretClass::RocketLauncher, WeaponStats { damage: 10, kills: 1 });
+        stats.weapons.insert(TurretClass::AutoCannon, WeaponStats { damage: 10, kills: 1 });
+        stats.weapons.insert(TurretClass::Emp, WeaponStats { damage: 10, kills: 3 });
+        stats.weapons.insert(TurretClass::BlastLaser, WeaponStats { damage: 20, kills: 0 });
+        let order: Vec<TurretClass> = stats.weapons_by_damage().into_iter().map(|(class, _)| class).collect();
+        assert!(order == [TurretClass::BlastLaser, TurretClass::Emp, TurretClass::AutoCannon, TurretClass::RocketLauncher]);
+    }
+}
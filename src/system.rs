@@ -12,6 +12,10 @@ mod loot;
 mod hit_flash;
 mod floating_text;
 mod rotator;
+mod interpolation;
+mod spatial_index;
+mod run_stats;
+mod status;
 
 pub use engine::*;
 pub use physics::*;
@@ -26,4 +30,8 @@ pub use death::*;
 pub use loot::*;
 pub use hit_flash::*;
 pub use floating_text::*;
-pub use rotator::*;
\ No newline at end of file
+pub use rotator::*;
+pub use interpolation::*;
+pub use spatial_index::*;
+pub use run_stats::*;
+pub use status::*;
\ No newline at end of file
@@ -1,34 +1,26 @@
-use crate::{component::*, resource::TakeDamageEvent};
+use crate::{component::*, resource::TakeDamageEvent, util::{SpatialEntry, SpatialIndex, SpatialLayer}};
 use bevy::prelude::*;
 
+type PotentialFilter = (Without<Bullet>, With<Collider>, With<Health>);
+
+const DAMAGEABLE_LAYERS: [SpatialLayer; 3] = [SpatialLayer::Player, SpatialLayer::Enemy, SpatialLayer::Other];
+
 pub fn bullet_system(
     mut commands: Commands,
     time: Res<Time>,
-    mut query: Query<(&mut Bullet, Entity, &Transform, &Owner, Option<&AoeDamage>), (With<Bullet>, Without<ShouldDespawn>)>,
-    potential_query: Query<
-        (&Collider, &Transform, Entity),
-        (Without<Bullet>, With<Collider>, With<Health>),
-    >,
+    mut query: Query<(&mut Bullet, Entity, &Transform, &Owner, Option<&AoeDamage>, Option<&FiredBy>, Option<&AppliesStatus>), (With<Bullet>, Without<ShouldDespawn>)>,
+    potential_query: Query<Entity, PotentialFilter>,
+    index: Res<SpatialIndex>,
     mut take_damage_event: EventWriter<TakeDamageEvent>,
 ) {
-    for (mut bullet, entity, transform, owner, aoe_damage) in &mut query {
+    for (mut bullet, entity, transform, owner, aoe_damage, fired_by, status) in &mut query {
         bullet.ttl.tick(time.delta());
         if bullet.ttl.just_finished() {
             // If timed out Aoe damage should still occur
             if let Some(aoe_damage) = aoe_damage {
-                let potentials = potential_query
-                    .iter()
-                    .filter_map(|potential| {
-                        if potential.2 == owner.0 {
-                            // Source of bullet cannot be hit
-                            return None;
-                        }
-                        Some(potential)
-                    })
-                    .collect::<Vec<_>>();
                 do_aoe_damage(
-                    potentials,
-                    (&mut bullet, transform, aoe_damage),
+                    potentials_in_range(&index, &potential_query, owner, transform, aoe_damage.range),
+                    (&mut bullet, aoe_damage, bullet_source(owner, fired_by, status)),
                     &mut take_damage_event,
                 );
             }
@@ -49,53 +41,38 @@ pub fn bullet_collision_system(
             Option<&DirectDamage>,
             Option<&AoeDamage>,
             &mut Bullet,
+            Option<&FiredBy>,
+            Option<&AppliesStatus>,
         ),
         (With<Bullet>, With<Collider>, With<Owner>, Without<ShouldDespawn>),
     >,
-    potential_query: Query<
-        (&Collider, &Transform, Entity),
-        (Without<Bullet>, With<Collider>, With<Health>),
-    >,
+    potential_query: Query<Entity, PotentialFilter>,
+    index: Res<SpatialIndex>,
     mut take_damage_event: EventWriter<TakeDamageEvent>,
 ) {
-    for (collider, transform, entity, owner, direct_damage, aoe_damage, mut bullet) in &mut query {
-        // Get all potentials
-        let potentials = potential_query
-            .iter()
-            .filter_map(|potential| {
-                if potential.2 == owner.0 {
-                    // Source of bullet cannot be hit
-                    return None;
-                }
-                Some(potential)
-            })
-            .collect::<Vec<_>>();
-
-        // Sort by distance to bullet
-        let hit = potentials.iter().find(|potential| {
-            transform
-                .translation
-                .truncate()
-                .distance(potential.1.translation.truncate())
-                <= collider.radius + potential.0.radius
-                && bullet.entities_hit.get(&potential.2).unwrap_or(&0) < &bullet.max_hits_per_entity
-        });
+    for (collider, transform, entity, owner, direct_damage, aoe_damage, mut bullet, fired_by, status) in &mut query {
+        // Get all potentials touching the bullet
+        let hit = potentials_in_range(&index, &potential_query, owner, transform, collider.radius)
+            .into_iter()
+            .find(|potential| bullet.entities_hit.get(&potential.entity).unwrap_or(&0) < &bullet.max_hits_per_entity);
 
-        if let Some((_collider, _transform, potential_entity)) = hit {
+        if let Some(potential) = hit {
             if let Some(direct_damage) = direct_damage {
-                let number_of_times_hit = bullet.entities_hit.entry(*potential_entity).or_insert(0);
+                let number_of_times_hit = bullet.entities_hit.entry(potential.entity).or_insert(0);
                 *number_of_times_hit += 1;
 
                 take_damage_event.send(TakeDamageEvent {
-                    entity: *potential_entity,
+                    entity: potential.entity,
                     damage: direct_damage.0,
+                    source: bullet_source(owner, fired_by, status),
+                    origin: Some(transform.translation.truncate()),
                 });
             }
 
             if let Some(aoe_damage) = aoe_damage {
                 do_aoe_damage(
-                    potentials,
-                    (&mut bullet, transform, aoe_damage),
+                    potentials_in_range(&index, &potential_query, owner, transform, aoe_damage.range),
+                    (&mut bullet, aoe_damage, bullet_source(owner, fired_by, status)),
                     &mut take_damage_event,
                 );
             }
@@ -108,30 +85,52 @@ pub fn bullet_collision_system(
     }
 }
 
+fn potentials_in_range(
+    index: &SpatialIndex,
+    potential_query: &Query<Entity, PotentialFilter>,
+    owner: &Owner,
+    transform: &Transform,
+    range: f32,
+) -> Vec<SpatialEntry> {
+    index
+        .query_circle(&DAMAGEABLE_LAYERS, transform.translation.truncate(), range)
+        .into_iter()
+        // Source of bullet cannot be hit
+        .filter(|potential| potential.entity != owner.0 && potential_query.contains(potential.entity))
+        .collect()
+}
+
 fn do_aoe_damage(
-    potentials: Vec<(&Collider, &Transform, Entity)>,
-    bullet: (&mut Bullet, &Transform, &AoeDamage),
+    potentials: Vec<SpatialEntry>,
+    bullet: (&mut Bullet, &AoeDamage, DamageSource),
     take_damage_event: &mut EventWriter<TakeDamageEvent>,
 ) {
-    let (bullet, transform, aoe_damage) = bullet;
+    let (bullet, aoe_damage, source) = bullet;
     let all_hits: Vec<_> = potentials
         .iter()
-        .filter(|potential| {
-            transform
-                .translation
-                .truncate()
-                .distance(potential.1.translation.truncate())
-                <= aoe_damage.range + potential.0.radius
-                && bullet.entities_hit.get(&potential.2).unwrap_or(&0) < &bullet.max_hits_per_entity
-        })
+        .filter(|potential| bullet.entities_hit.get(&potential.entity).unwrap_or(&0) < &bullet.max_hits_per_entity)
         .collect();
     for h in all_hits.iter() {
-        let number_of_times_hit = bullet.entities_hit.entry(h.2).or_insert(0);
+        let number_of_times_hit = bullet.entities_hit.entry(h.entity).or_insert(0);
         *number_of_times_hit += 1;
 
         take_damage_event.send(TakeDamageEvent {
-            entity: h.2,
+            entity: h.entity,
             damage: aoe_damage.damage,
+            source,
+            // The explosion pushes instead
+            origin: None,
         });
     }
-}
\ No newline at end of file
+}
+
+fn bullet_source(owner: &Owner, fired_by: Option<&FiredBy>, status: Option<&AppliesStatus>) -> DamageSource {
+    let source = match fired_by {
+        Some(fired_by) => DamageSource::weapon(owner.0, fired_by.0),
+        None => DamageSource { attacker: Some(owner.0), ..Default::default() },
+    };
+    match status {
+        Some(status) => source.with_status(status),
+        None => source,
+    }
+}
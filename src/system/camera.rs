@@ -1,9 +1,10 @@
-use crate::{component::*, util::Math};
+use crate::{component::*, plugin::Settings, util::Math};
 use bevy::prelude::*;
 use bevy_parallax::ParallaxMoveEvent;
 
 pub fn camera_follow(
     time: Res<Time>,
+    settings: Res<Settings>,
     player_q: Query<&Transform, (With<Transform>, With<IsPlayer>, Without<MainCamera>)>,
     mut camera_q: Query<
         (Entity, &Transform, &mut CameraShake),
@@ -21,9 +22,10 @@ pub fn camera_follow(
 
             let current_position = camera_transform.translation.truncate();
 
+            // Shake is purely cosmetic so it must not draw from the seeded game rng
             let smooth_move_position = current_position
                 .lerp(target_position, 5.0 * time.delta_secs())
-                + shake.trauma * Math::random_2d_unit_vector();
+                + shake.trauma * settings.shake_scale * Math::random_2d_unit_vector(&mut rand::thread_rng());
 
             shake.trauma = f32::max(shake.trauma - shake.decay * time.delta_secs(), 0.0);
 
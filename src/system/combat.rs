@@ -1,10 +1,13 @@
 use crate::{
     component::*,
-    resource::{Fonts, TakeDamageEvent},
+    resource::{Fonts, RunStats, SoundEvent, TakeDamageEvent},
     util::{Colour, RenderLayer},
 };
 use bevy::prelude::*;
 
+/// Push per point of damage dealt on something with a mass of 1
+const KNOCKBACK_PER_DAMAGE: f32 = 25.0;
+
 pub fn combat_system(
     mut commands: Commands,
     time: Res<Time>,
@@ -30,6 +33,7 @@ pub fn combat_system(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn take_damage_events(
     mut commands: Commands,
     fonts: Res<Fonts>,
@@ -39,22 +43,47 @@ pub fn take_damage_events(
         &mut Health,
         Option<&IsPlayer>,
         Option<&mut HitFlash>,
+        Option<&mut StatusEffects>,
+        Option<&Resistance>,
+        Option<&mut Physics>,
     )>,
+    player_query: Query<(), With<IsPlayer>>,
     mut camera: Query<&mut CameraShake>,
+    mut sound_event: EventWriter<SoundEvent>,
+    mut stats: ResMut<RunStats>,
 ) {
     for ev in take_damage_events.read() {
-        if let Ok((transform, mut health, is_player, hit_flash)) = query.get_mut(ev.entity) {
-            health.take_damage(ev.damage.amount);
+        if let Ok((transform, mut health, is_player, hit_flash, status_effects, resistance, physics)) = query.get_mut(ev.entity) {
+            sound_event.send(if health.shield > 0 { SoundEvent::ShieldHit } else { SoundEvent::ArmourHit });
+            let (to_shield, to_armour) = health.take_damage(ev.damage.amount, ev.source.kind, resistance, status_effects.as_deref());
+            let dealt = to_shield + to_armour;
+            if let (Some(effect), Some(mut effects)) = (ev.source.status, status_effects) {
+                effects.apply(effect, ev.source);
+            }
+            if let (Some(origin), Some(mut physics)) = (ev.origin, physics) {
+                let away = (transform.translation.truncate() - origin).normalize_or_zero();
+                physics.add_impulse(away * dealt as f32 * KNOCKBACK_PER_DAMAGE);
+            }
+
+            commands.entity(ev.entity).insert(LastHitBy(ev.source));
+            if is_player.is_some() {
+                stats.shield_damage_taken += to_shield as u32;
+                stats.armour_damage_taken += to_armour as u32;
+            } else if let (Some(attacker), Some(class)) = (ev.source.attacker, ev.source.class) {
+                if player_query.contains(attacker) {
+                    stats.weapons.entry(class).or_default().damage += dealt as u32;
+                }
+            }
 
             if is_player.is_some() {
                 if let Ok(mut shake) = camera.get_single_mut() {
-                    shake.trauma = ev.damage.amount.clamp(0, 5) as f32;
+                    shake.trauma = dealt.clamp(0, 5) as f32;
                 }
             } else {
                 // Floating Text
                 commands.spawn((
                     FloatingText::default(),
-                    Text2d::new(format!("{}", ev.damage.amount)),
+                    Text2d::new(format!("{}", dealt)),
                     TextFont {
                         font: fonts.primary.clone(),
                         font_size: if ev.damage.is_crit { 14.0 } else { 12.0 },
@@ -1,8 +1,9 @@
-use crate::{component::*, resource::{Fonts, Points}, GameState, util::{Colour, RenderLayer}};
+use crate::{component::*, plugin::ArchetypeName, resource::{Fonts, GameRng, KillEvent, Points, SoundEvent}, GameState, util::{Colour, RenderLayer}};
 use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
 use rand::prelude::*;
 
+#[allow(clippy::too_many_arguments)]
 pub fn death_system(
     mut commands: Commands,
     fonts: Res<Fonts>,
@@ -14,21 +15,38 @@ pub fn death_system(
             Option<&IsPlayer>,
             Option<&ExplodesOnDespawn>,
             Option<&WorthPoints>,
+            Option<&Targettable>,
+            Option<&LastHitBy>,
+            Option<&ArchetypeName>,
+            Option<&Owner>,
         ),
         With<ShouldDespawn>,
     >,
     mut game_state: ResMut<NextState<GameState>>,
     mut points: ResMut<Points>,
+    mut rng: ResMut<GameRng>,
+    mut sound_event: EventWriter<SoundEvent>,
+    mut kill_event: EventWriter<KillEvent>,
 ) {
-    for (entity, drops_loot, transform, is_player, explodes, worth_points) in &mut query {
+    for (entity, drops_loot, transform, is_player, explodes, worth_points, targettable, last_hit, archetype, owner) in &mut query {
         commands.entity(entity).despawn_recursive();
 
+        // Kill credit goes to whatever landed the last hit
+        if let Some(LastHitBy(source)) = last_hit {
+            kill_event.send(KillEvent { entity, source: *source, archetype: archetype.map(|archetype| archetype.0.clone()) });
+        }
+
+        if let Some(Targettable(Allegiance::ENEMY)) = targettable {
+            sound_event.send(SoundEvent::EnemyDeath);
+        }
+
         if let Some(transform) = transform {
             if let Some(_drops_loot) = drops_loot {
-                spawn_loot(&mut commands, &fonts, transform.translation);
+                spawn_loot(&mut commands, &fonts, &mut rng, transform.translation);
             }
             if let Some(explodes) = explodes {
-                explode(&mut commands, explodes, transform.translation.truncate());
+                // Bullets don't push the ship that fired them
+                explode(&mut commands, &mut rng, explodes, transform.translation.truncate(), owner.map(|owner| owner.0));
             }
         }
 
@@ -42,26 +60,34 @@ pub fn death_system(
     }
 }
 
-fn spawn_loot(commands: &mut Commands, fonts: &Res<Fonts>, position: Vec3) {
-    let mut rng = rand::thread_rng();
+/// Experience left behind by destroyed ships, picked up for points
+pub fn loot_bundle(fonts: &Fonts, transform: Transform, velocity: Vec2, acceleration: Vec2) -> impl Bundle {
+    (
+        IsLoot,
+        GlyphBundle::new("*", Colour::PURPLE, 12.0, fonts.primary.clone()),
+        transform,
+        Physics {
+            velocity,
+            acceleration,
+            drag: 1.0,
+            ..Default::default()
+        },
+        Collider { radius: 20.0 },
+        DespawnWithScene,
+    )
+}
+
+fn spawn_loot(commands: &mut Commands, fonts: &Res<Fonts>, rng: &mut GameRng, position: Vec3) {
     let loots = (0..rng.gen_range(1..=3))
         .map(|_| {
+            let acceleration = Vec2 {
+                x: rng.gen_range(-1.0..1.0),
+                y: rng.gen_range(-1.0..1.0),
+            }
+            .normalize_or_zero()
+                * rng.gen_range(50.0..100.0);
             (
-                IsLoot,
-                GlyphBundle::new("*", Colour::PURPLE, 12.0, fonts.primary.clone()),
-                Transform::from_translation(position),
-                Physics {
-                    acceleration: Vec2 {
-                        x: rng.gen_range(-1.0..1.0),
-                        y: rng.gen_range(-1.0..1.0),
-                    }
-                    .normalize_or_zero()
-                        * rng.gen_range(50.0..100.0),
-                    drag: 1.0,
-                    ..Default::default()
-                },
-                Collider { radius: 20.0 },
-                DespawnWithScene,
+                loot_bundle(fonts, Transform::from_translation(position), Vec2::ZERO, acceleration),
                 WorthPoints { value: 1 },
             )
         })
@@ -69,9 +95,8 @@ fn spawn_loot(commands: &mut Commands, fonts: &Res<Fonts>, position: Vec3) {
     commands.spawn_batch(loots);
 }
 
-fn explode(commands: &mut Commands, explodes: &ExplodesOnDespawn, position: Vec2) {
+fn explode(commands: &mut Commands, rng: &mut GameRng, explodes: &ExplodesOnDespawn, position: Vec2, ignore: Option<Entity>) {
     // Spawn several explosions
-    let mut rng = rand::thread_rng();
     let amount = rng.gen_range(explodes.amount_min..=explodes.amount_max);
     for _ in 0..amount {
       let offset = Vec2 { x: rng.gen_range(-explodes.spread..=explodes.spread), y: rng.gen_range(-explodes.spread..=explodes.spread) };
@@ -82,6 +107,7 @@ fn explode(commands: &mut Commands, explodes: &ExplodesOnDespawn, position: Vec2
               ttl: Timer::from_seconds(rng.gen_range(explodes.duration_min..=explodes.duration_max), TimerMode::Once),
               fade_out: false,
           },
+          Shockwave { force: explodes.force / amount.max(1) as f32, ignore },
           ShapeBundle {
               path: GeometryBuilder::build_as(&shapes::Circle {
                   center: position,
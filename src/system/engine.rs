@@ -5,14 +5,16 @@ use crate::component::*;
 
 pub fn engine_system(
   time: Res<Time>,
-  mut query: Query<(&Transform, &mut Physics, &mut Engine), (With<Transform>, With<Physics>, With<Engine>)>,
+  mut query: Query<(&Transform, &mut Physics, &mut Engine, Option<&StatusEffects>), (With<Transform>, With<Physics>, With<Engine>)>,
 ) {
-  for (transform, mut physics, mut engine) in &mut query {
+  for (transform, mut physics, mut engine, status_effects) in &mut query {
       
       let current = transform.translation.truncate();
-      if let Some(target) = engine.target {
+      let stunned = status_effects.is_some_and(|effects| effects.is_stunned());
+      let max_speed = engine.max_speed * status_effects.map(|effects| effects.speed_scale()).unwrap_or(1.0);
+      if let (Some(target), false) = (engine.target, stunned) {
           engine.speed += engine.power * time.delta_secs();
-          if engine.speed > engine.max_speed { engine.speed = engine.max_speed; }
+          if engine.speed > max_speed { engine.speed = max_speed; }
           let to_target = match engine.method {
             EngineMethod::Approach => approach(current, target),
             EngineMethod::Keep(distance) => keep_at_distance(current, target, distance),
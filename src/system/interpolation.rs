@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+use crate::component::*;
+
+// Put the simulated translation back before any fixed steps run
+pub fn restore_translation_system(
+  mut query: Query<(&mut Transform, &TranslationInterpolation)>,
+) {
+  for (mut transform, interpolation) in &mut query {
+    if let Some(current) = interpolation.current {
+      transform.translation = current;
+    }
+  }
+}
+
+pub fn store_previous_translation_system(
+  mut query: Query<(&Transform, &mut TranslationInterpolation)>,
+) {
+  for (transform, mut interpolation) in &mut query {
+    interpolation.previous = Some(transform.translation);
+  }
+}
+
+// Render part way between the last two fixed steps so movement is smooth at any frame rate
+pub fn interpolate_translation_system(
+  fixed_time: Res<Time<Fixed>>,
+  mut query: Query<(&mut Transform, &mut TranslationInterpolation)>,
+) {
+  let blend = fixed_time.overstep_fraction();
+  for (mut transform, mut interpolation) in &mut query {
+    interpolation.current = Some(transform.translation);
+    if let Some(previous) = interpolation.previous {
+      transform.translation = previous.lerp(transform.translation, blend);
+    }
+  }
+}
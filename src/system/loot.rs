@@ -1,13 +1,15 @@
 use bevy::prelude::*;
 use rand::Rng;
-use crate::{component::*, resource::Points};
+use crate::{component::*, resource::{GameRng, Points, RunStats, SoundEvent}, util::{SpatialIndex, SpatialLayer}};
 
 pub fn loot_magnet_system(
+  index: Res<SpatialIndex>,
   query: Query<(&Magnet, &Transform), (With<Magnet>, With<Transform>)>,
   mut loot_query: Query<(&mut Physics, &Transform), (With<IsLoot>, With<Physics>, With<Transform>, Without<Magnet>)>
 ) {
   for (magnet, transform) in &query {
-    for (mut physics, loot_transform) in &mut loot_query {
+    for loot in index.query_circle(&[SpatialLayer::Loot], transform.translation.truncate(), magnet.range) {
+      let Ok((mut physics, loot_transform)) = loot_query.get_mut(loot.entity) else { continue; };
       if loot_transform.translation.truncate().distance(transform.translation.truncate()) > magnet.range {
         continue;
       }
@@ -18,22 +20,32 @@ pub fn loot_magnet_system(
   }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn loot_cargo_collision(
   mut commands: Commands,
+  index: Res<SpatialIndex>,
   mut query: Query<(&mut Cargo, &Transform, &Collider), (With<Cargo>, With<Transform>, With<Collider>)>,
   loot_query: Query<(&Transform, Entity, &Collider, Option<&WorthPoints>), (With<IsLoot>, With<Transform>, With<Collider>, Without<Cargo>)>,
   mut points: ResMut<Points>,
+  mut rng: ResMut<GameRng>,
+  mut sound_event: EventWriter<SoundEvent>,
+  mut stats: ResMut<RunStats>,
 ) {
   
   for (mut cargo, transform, collider) in &mut query {
-    for (loot_transform, loot_entity, loot_collider, worth_points) in &loot_query {
+    for loot in index.query_circle(&[SpatialLayer::Loot], transform.translation.truncate(), collider.radius) {
+      let Ok((loot_transform, loot_entity, loot_collider, worth_points)) = loot_query.get(loot.entity) else { continue; };
       if loot_transform.translation.truncate().distance(transform.translation.truncate()) <= loot_collider.radius + collider.radius {
         // Increase cargo
         cargo.amount += 1;
-        if rand::thread_rng().gen_range(0.0..1.0) < cargo.bonus_chance {
+        stats.loot_collected += 1;
+        if rng.gen_range(0.0..1.0) < cargo.bonus_chance {
           cargo.amount += 2;
+          stats.bonus_cargo += 1;
         }
 
+        sound_event.send(SoundEvent::LootPickup);
+
         // Add points
         if let Some(worth_points) = worth_points {
           points.value += worth_points.value;
@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use crate::{component::*, util::Math};
+use crate::{component::*, util::{Math, SpatialIndex, SpatialLayer}};
 
 pub fn physics_system(
   time: Res<Time>,
@@ -23,4 +23,29 @@ pub fn physics_system(
           transform.rotation *= base_rotation.rotation; // Multiplication is like combining rotations together
       }
   }
-}
\ No newline at end of file
+}
+
+/// Push everything within a fresh explosion away from its centre, weaker towards the edge
+pub fn shockwave_system(
+  mut commands: Commands,
+  index: Res<SpatialIndex>,
+  query: Query<(Entity, &ExplosionRender, &Shockwave)>,
+  mut physics_query: Query<(&Transform, &mut Physics)>,
+) {
+  const LAYERS: [SpatialLayer; 4] = [SpatialLayer::Player, SpatialLayer::Enemy, SpatialLayer::Loot, SpatialLayer::Other];
+  for (entity, explosion, shockwave) in &query {
+      commands.entity(entity).remove::<Shockwave>();
+      if explosion.radius <= 0.0 {
+          continue;
+      }
+      for hit in index.query_circle(&LAYERS, explosion.origin, explosion.radius) {
+          if Some(hit.entity) == shockwave.ignore {
+              continue;
+          }
+          let Ok((transform, mut physics)) = physics_query.get_mut(hit.entity) else { continue; };
+          let offset = transform.translation.truncate() - explosion.origin;
+          let falloff = (1.0 - offset.length() / explosion.radius).clamp(0.0, 1.0);
+          physics.add_impulse(offset.normalize_or_zero() * shockwave.force * falloff);
+      }
+  }
+}
@@ -1,14 +1,21 @@
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
-use crate::{component::*, input::PlayerAction, resource::PlayerLevel, GameState};
+use crate::{component::*, input::PlayerAction, plugin::SelectionCharges, resource::{PlayerLevel, SoundEvent}, GameState};
+
+/// How far ahead of the ship the engine aims when steering with keys or a stick
+const STEER_DISTANCE: f32 = 200.0;
 
 pub fn player_control(
   windows: Query<&Window>,
   camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-  mut query: Query<(&IsPlayer, &mut Engine, &ActionState<PlayerAction>), (With<IsPlayer>, With<Engine>, With<ActionState<PlayerAction>>)>,
+  action_state: Res<ActionState<PlayerAction>>,
+  mut query: Query<(&Transform, &mut Engine), (With<IsPlayer>, With<Engine>)>,
 ) {
-  for (_, mut engine, action_state) in &mut query {
-      if action_state.pressed(&PlayerAction::Move) {
+  let direction = PlayerAction::direction(&action_state);
+  for (transform, mut engine) in &mut query {
+      if direction != Vec2::ZERO {
+          engine.target = Some(transform.translation.truncate() + direction * STEER_DISTANCE);
+      } else if action_state.pressed(&PlayerAction::Move) {
           // Calculate current position to mouse position
           let (camera, camera_transform) = camera_q.single();
           let window = windows.get_single().expect("no primary window");
@@ -23,43 +30,39 @@ pub fn player_control(
 }
 
 pub fn pause_control(
-  key_input: Res<ButtonInput<KeyCode>>,
+  action_state: Res<ActionState<PlayerAction>>,
   game_state: Res<State<GameState>>,
   mut change_game_state: ResMut<NextState<GameState>>,
-  mut query: Query<&mut CameraShake>,
 ) {
-  if key_input.just_pressed(KeyCode::Escape) {
+  if action_state.just_pressed(&PlayerAction::Pause) {
     match game_state.get() {
       GameState::Running => change_game_state.set(GameState::Paused),
       GameState::Paused => change_game_state.set(GameState::Running),
       _ => ()
     }
   }
-
-  // Debug camera shake
-  if key_input.just_pressed(KeyCode::KeyR) {
-    for mut shake in &mut query {
-      shake.trauma = 5.0;
-    }
-  }
 }
 
 pub fn level_up_system(
   mut level: ResMut<PlayerLevel>,
+  mut charges: ResMut<SelectionCharges>,
   mut query: Query<&mut Cargo, With<IsPlayer>>,
   mut next_state: ResMut<NextState<GameState>>,
+  mut sound_event: EventWriter<SoundEvent>,
 ) {
   for mut cargo in &mut query {
     if cargo.amount >= level.required_cargo_to_level() {
       cargo.amount -= level.required_cargo_to_level();
       level.value += 1;
+      charges.earn(level.value);
+      sound_event.send(SoundEvent::LevelUp);
       next_state.set(GameState::Selection);
     }
   }
 }
 
 pub fn zoom_control(
-  key_input: Res<ButtonInput<KeyCode>>,
+  action_state: Res<ActionState<PlayerAction>>,
   mut camera_q: Query<
         &mut OrthographicProjection,
         (With<OrthographicProjection>, With<MainCamera>),
@@ -67,13 +70,13 @@ pub fn zoom_control(
 ) {
   let scale_factor = 0.25;
 
-  if key_input.just_pressed(KeyCode::NumpadAdd) {
+  if action_state.just_pressed(&PlayerAction::ZoomIn) {
     if let Ok(mut projection) = camera_q.get_single_mut() {
       projection.scale = (projection.scale - scale_factor).max(1.);
     }
   }
 
-  if key_input.just_pressed(KeyCode::NumpadSubtract) {
+  if action_state.just_pressed(&PlayerAction::ZoomOut) {
     if let Ok(mut projection) = camera_q.get_single_mut() {
       projection.scale = (projection.scale + scale_factor).min(3.);
     }
@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+use crate::{component::*, resource::{KillEvent, RunStats}};
+
+pub fn run_stats_system(
+    time: Res<Time>,
+    mut stats: ResMut<RunStats>,
+    player_query: Query<&Physics, With<IsPlayer>>,
+    targettable_query: Query<&Targettable>,
+) {
+    for physics in &player_query {
+        stats.distance += physics.velocity.length() * time.delta_secs();
+    }
+
+    let enemies = targettable_query.iter().filter(|targettable| targettable.0 == Allegiance::ENEMY).count() as u32;
+    stats.peak_enemies = stats.peak_enemies.max(enemies);
+}
+
+/// Count kills per enemy archetype, and per weapon when the player landed the last hit
+pub fn kill_stats_system(
+    mut kill_event: EventReader<KillEvent>,
+    mut stats: ResMut<RunStats>,
+    player_query: Query<(), With<IsPlayer>>,
+) {
+    for ev in kill_event.read() {
+        let Some(archetype) = &ev.archetype else { continue; };
+        *stats.kills.entry(archetype.clone()).or_default() += 1;
+        let killed_by_player = ev.source.attacker.is_some_and(|attacker| player_query.contains(attacker));
+        if killed_by_player {
+            if let Some(class) = ev.source.class {
+                stats.weapons.entry(class).or_default().kills += 1;
+            }
+        }
+    }
+}
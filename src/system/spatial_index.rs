@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+use crate::{component::*, util::{SpatialIndex, SpatialLayer}};
+
+pub fn spatial_index_system(
+  mut index: ResMut<SpatialIndex>,
+  query: Query<(Entity, &Transform, &Collider, Option<&Targettable>, Has<IsLoot>), Without<Bullet>>,
+) {
+  index.clear();
+  for (entity, transform, collider, targettable, is_loot) in &query {
+    let layer = match (targettable, is_loot) {
+      (_, true) => SpatialLayer::Loot,
+      (Some(targettable), false) => SpatialLayer::from_allegiance(&targettable.0),
+      (None, false) => SpatialLayer::Other,
+    };
+    index.insert(layer, entity, transform.translation.truncate(), collider.radius);
+  }
+}
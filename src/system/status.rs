@@ -0,0 +1,88 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{component::*, resource::{Fonts, TakeDamageEvent}, util::RenderLayer};
+
+/// Floats above a ship showing a glyph for each of its status effects
+#[derive(Component)]
+pub struct StatusOverlay(pub Entity);
+
+pub fn status_effect_system(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut StatusEffects)>,
+    mut take_damage_event: EventWriter<TakeDamageEvent>,
+) {
+    for (entity, mut effects) in &mut query {
+        if effects.active.is_empty() {
+            continue;
+        }
+        for active in &mut effects.active {
+            active.remaining.tick(time.delta());
+            if active.kind != StatusKind::Burn {
+                continue;
+            }
+            active.tick.tick(time.delta());
+            if active.tick.just_finished() {
+                take_damage_event.send(TakeDamageEvent {
+                    entity,
+                    damage: Damage { amount: (active.potency * active.stacks as f32).round().max(1.0) as i32, is_crit: false },
+                    // Burning doesn't set itself alight again
+                    source: DamageSource { status: None, ..active.source },
+                    origin: None,
+                });
+            }
+        }
+        effects.active.retain(|active| !active.remaining.finished());
+    }
+}
+
+pub fn status_overlay_system(
+    mut commands: Commands,
+    fonts: Res<Fonts>,
+    ship_query: Query<(Entity, &Transform, &StatusEffects, Option<&Collider>), Without<StatusOverlay>>,
+    mut overlay_query: Query<(Entity, &StatusOverlay, &mut Transform, &Children)>,
+    mut span_query: Query<&mut TextSpan>,
+) {
+    let mut shown = HashSet::new();
+    for (overlay_entity, overlay, mut transform, children) in &mut overlay_query {
+        let Some((_, ship_transform, effects, collider)) = ship_query.get(overlay.0).ok().filter(|(_, _, effects, _)| !effects.active.is_empty()) else {
+            commands.entity(overlay_entity).despawn_recursive();
+            continue;
+        };
+        shown.insert(overlay.0);
+        transform.translation = overlay_position(ship_transform, collider);
+        for (kind, child) in StatusKind::ALL.iter().zip(children.iter()) {
+            if let Ok(mut span) = span_query.get_mut(*child) {
+                span.0 = if effects.has(*kind) { kind.glyph().to_string() } else { "".to_string() };
+            }
+        }
+    }
+
+    for (entity, ship_transform, effects, collider) in &ship_query {
+        if effects.active.is_empty() || shown.contains(&entity) {
+            continue;
+        }
+        commands
+            .spawn((
+                StatusOverlay(entity),
+                Text2d::default(),
+                TextFont { font: fonts.primary.clone(), font_size: 12.0, ..default() },
+                TextLayout::new_with_justify(JustifyText::Center),
+                Transform::from_translation(overlay_position(ship_transform, collider)),
+                DespawnWithScene,
+            ))
+            .with_children(|parent| {
+                for kind in StatusKind::ALL {
+                    parent.spawn((
+                        TextSpan::new(if effects.has(kind) { kind.glyph() } else { "" }),
+                        TextFont { font: fonts.primary.clone(), font_size: 12.0, ..default() },
+                        TextColor(kind.colour()),
+                    ));
+                }
+            });
+    }
+}
+
+fn overlay_position(ship_transform: &Transform, collider: Option<&Collider>) -> Vec3 {
+    let offset = collider.map(|collider| collider.radius).unwrap_or(10.0) + 8.0;
+    Vec3::new(ship_transform.translation.x, ship_transform.translation.y + offset, RenderLayer::Effects.as_z())
+}
@@ -1,7 +1,13 @@
 mod colour;
 mod layer;
 mod math;
+mod ron_asset;
+mod spatial;
+mod storage;
 
 pub use colour::*;
 pub use layer::*;
 pub use math::*;
+pub use ron_asset::*;
+pub use spatial::*;
+pub use storage::*;
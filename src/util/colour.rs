@@ -15,4 +15,22 @@ impl Colour {
     pub const YELLOW: Color = Color::Srgba (Srgba { red: 237.0 / 255.0, green: 225.0 / 255.0, blue: 158.0 / 255.0, alpha: 1.0 });
     pub const PURPLE: Color = Color::Srgba (Srgba { red: 138.0 / 255.0, green: 112.0 / 255.0, blue: 225.0 / 255.0, alpha: 1.0 });
     pub const PINK: Color = Color::Srgba (Srgba { red: 255.0 / 255.0, green: 113.0 / 255.0, blue: 159.0 / 255.0, alpha: 1.0 });
+
+    /// Look up a constant by name so data files can refer to the palette
+    pub fn named(name: &str) -> Option<Color> {
+        match name {
+            "BLACK" => Some(Colour::BLACK),
+            "PLAYER" => Some(Colour::PLAYER),
+            "WHITE" => Some(Colour::WHITE),
+            "SHIELD" => Some(Colour::SHIELD),
+            "INACTIVE" => Some(Colour::INACTIVE),
+            "ENEMY" => Some(Colour::ENEMY),
+            "RED" => Some(Colour::RED),
+            "GREEN" => Some(Colour::GREEN),
+            "YELLOW" => Some(Colour::YELLOW),
+            "PURPLE" => Some(Colour::PURPLE),
+            "PINK" => Some(Colour::PINK),
+            _ => None,
+        }
+    }
 }
\ No newline at end of file
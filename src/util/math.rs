@@ -8,8 +8,7 @@ impl Math {
     Quat::from_rotation_z(angle)
   }
 
-  pub fn random_2d_unit_vector() -> Vec2 {
-    let mut rng = rand::thread_rng();
+  pub fn random_2d_unit_vector(rng: &mut impl Rng) -> Vec2 {
     Vec2 {
         x: rng.gen_range(-1.0..1.0),
         y: rng.gen_range(-1.0..1.0),
@@ -0,0 +1,32 @@
+use std::marker::PhantomData;
+
+use bevy::{asset::{io::Reader, AssetLoader, LoadContext}, prelude::*};
+use serde::de::DeserializeOwned;
+
+/// Loads any asset that can be deserialized from a RON file
+pub struct RonAssetLoader<A> {
+    _asset: PhantomData<fn() -> A>,
+}
+
+impl<A> Default for RonAssetLoader<A> {
+    fn default() -> Self {
+        Self { _asset: PhantomData }
+    }
+}
+
+impl<A: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(&self, reader: &mut dyn Reader, _settings: &(), _load_context: &mut LoadContext<'_>) -> Result<A, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    // Several assets share the extension, the loader is picked by the requested asset type
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
@@ -0,0 +1,172 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::component::Allegiance;
+
+/// Groups kept apart in the index so a query only looks at what it cares about
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SpatialLayer {
+    Player,
+    Enemy,
+    Loot,
+    Other,
+}
+
+impl SpatialLayer {
+    pub fn from_allegiance(allegiance: &Allegiance) -> Self {
+        match allegiance {
+            Allegiance::PLAYER => SpatialLayer::Player,
+            Allegiance::ENEMY => SpatialLayer::Enemy,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct SpatialEntry {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub radius: f32,
+}
+
+/// Uniform grid of colliders rebuilt every simulation step.
+/// Finds what is near a point without checking every entity.
+#[derive(Resource)]
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<(SpatialLayer, IVec2), Vec<SpatialEntry>>,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        SpatialIndex::new(100.0)
+    }
+}
+
+impl SpatialIndex {
+    pub fn new(cell_size: f32) -> Self {
+        Self { cell_size, cells: HashMap::new() }
+    }
+
+    fn cell(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+
+    /// Empty the index, keeping the storage of cells that were used last time
+    pub fn clear(&mut self) {
+        self.cells.retain(|_, entries| {
+            let used = !entries.is_empty();
+            entries.clear();
+            used
+        });
+    }
+
+    /// Add a collider to every cell it overlaps
+    pub fn insert(&mut self, layer: SpatialLayer, entity: Entity, position: Vec2, radius: f32) {
+        let min = self.cell(position - Vec2::splat(radius));
+        let max = self.cell(position + Vec2::splat(radius));
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.cells
+                    .entry((layer, IVec2::new(x, y)))
+                    .or_default()
+                    .push(SpatialEntry { entity, position, radius });
+            }
+        }
+    }
+
+    /// Colliders on the given layers that overlap the circle, each entity is returned once
+    pub fn query_circle(&self, layers: &[SpatialLayer], center: Vec2, radius: f32) -> Vec<SpatialEntry> {
+        let min = self.cell(center - Vec2::splat(radius));
+        let max = self.cell(center + Vec2::splat(radius));
+        let mut found = vec![];
+        for layer in layers {
+            for x in min.x..=max.x {
+                for y in min.y..=max.y {
+                    let cell = IVec2::new(x, y);
+                    let Some(entries) = self.cells.get(&(*layer, cell)) else { continue; };
+                    for entry in entries {
+                        // Colliders spanning several cells are only reported from the first cell shared with the query
+                        let first_shared = self.cell(entry.position - Vec2::splat(entry.radius)).max(min);
+                        if first_shared != cell {
+                            continue;
+                        }
+                        if entry.position.distance(center) <= radius + entry.radius {
+                            found.push(*entry);
+                        }
+                    }
+                }
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entities(found: &[SpatialEntry]) -> Vec<Entity> {
+        let mut entities: Vec<Entity> = found.iter().map(|entry| entry.entity).collect();
+        entities.sort();
+        entities
+    }
+
+    #[test]
+    fn large_collider_is_found_once() {
+        let mut index = SpatialIndex::new(100.0);
+        let big = Entity::from_raw(1);
+        // Spans a 5x5 block of cells
+        index.insert(SpatialLayer::Enemy, big, Vec2::new(50.0, 50.0), 220.0);
+
+        // Query covering every cell the collider is in
+        let found = index.query_circle(&[SpatialLayer::Enemy], Vec2::new(50.0, 50.0), 300.0);
+        assert_eq!(entities(&found), vec![big]);
+
+        // Query starting part way through the collider's cells
+        let found = index.query_circle(&[SpatialLayer::Enemy], Vec2::new(230.0, 230.0), 60.0);
+        assert_eq!(entities(&found), vec![big]);
+    }
+
+    #[test]
+    fn negative_coordinates() {
+        let mut index = SpatialIndex::new(100.0);
+        let near = Entity::from_raw(1);
+        let far = Entity::from_raw(2);
+        let straddling = Entity::from_raw(3);
+        index.insert(SpatialLayer::Enemy, near, Vec2::new(-150.0, -150.0), 10.0);
+        index.insert(SpatialLayer::Enemy, far, Vec2::new(-450.0, -150.0), 10.0);
+        index.insert(SpatialLayer::Enemy, straddling, Vec2::new(0.0, -100.0), 30.0);
+
+        let found = index.query_circle(&[SpatialLayer::Enemy], Vec2::new(-120.0, -120.0), 50.0);
+        assert_eq!(entities(&found), vec![near]);
+
+        let found = index.query_circle(&[SpatialLayer::Enemy], Vec2::new(-50.0, -100.0), 30.0);
+        assert_eq!(entities(&found), vec![straddling]);
+    }
+
+    #[test]
+    fn only_requested_layers_are_queried() {
+        let mut index = SpatialIndex::new(100.0);
+        let player = Entity::from_raw(1);
+        let enemy = Entity::from_raw(2);
+        let loot = Entity::from_raw(3);
+        index.insert(SpatialLayer::Player, player, Vec2::ZERO, 10.0);
+        index.insert(SpatialLayer::Enemy, enemy, Vec2::new(5.0, 0.0), 10.0);
+        index.insert(SpatialLayer::Loot, loot, Vec2::new(0.0, 5.0), 10.0);
+
+        let found = index.query_circle(&[SpatialLayer::Enemy], Vec2::ZERO, 20.0);
+        assert_eq!(entities(&found), vec![enemy]);
+
+        let found = index.query_circle(&[SpatialLayer::Player, SpatialLayer::Loot], Vec2::ZERO, 20.0);
+        assert_eq!(entities(&found), vec![player, loot]);
+
+        assert!(index.query_circle(&[SpatialLayer::Other], Vec2::ZERO, 20.0).is_empty());
+    }
+
+    #[test]
+    fn clear_empties_the_index() {
+        let mut index = SpatialIndex::new(100.0);
+        index.insert(SpatialLayer::Enemy, Entity::from_raw(1), Vec2::ZERO, 10.0);
+        index.clear();
+        assert!(index.query_circle(&[SpatialLayer::Enemy], Vec2::ZERO, 50.0).is_empty());
+    }
+}
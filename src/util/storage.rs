@@ -0,0 +1,69 @@
+/// Small named text blobs that outlive the game.
+/// Stored as files in the working directory natively and in local storage in the browser.
+pub struct Storage;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage {
+  pub fn exists(key: &str) -> bool {
+    std::path::Path::new(key).exists()
+  }
+
+  pub fn read(key: &str) -> Result<String, String> {
+    std::fs::read_to_string(key).map_err(|err| err.to_string())
+  }
+
+  pub fn write(key: &str, contents: &str) -> Result<(), String> {
+    std::fs::write(key, contents).map_err(|err| err.to_string())
+  }
+
+  pub fn remove(key: &str) -> Result<(), String> {
+    if !Self::exists(key) {
+      return Ok(());
+    }
+    std::fs::remove_file(key).map_err(|err| err.to_string())
+  }
+
+  /// Seconds since the unix epoch
+  pub fn now() -> u64 {
+    std::time::SystemTime::now()
+      .duration_since(std::time::UNIX_EPOCH)
+      .map(|duration| duration.as_secs())
+      .unwrap_or_default()
+  }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage {
+  fn local_storage() -> Result<web_sys::Storage, String> {
+    web_sys::window()
+      .ok_or("no window")?
+      .local_storage()
+      .ok()
+      .flatten()
+      .ok_or_else(|| "local storage is unavailable".to_string())
+  }
+
+  pub fn exists(key: &str) -> bool {
+    Self::read(key).is_ok()
+  }
+
+  pub fn read(key: &str) -> Result<String, String> {
+    Self::local_storage()?
+      .get_item(key)
+      .map_err(|err| format!("{:?}", err))?
+      .ok_or_else(|| format!("{} not found", key))
+  }
+
+  pub fn write(key: &str, contents: &str) -> Result<(), String> {
+    Self::local_storage()?.set_item(key, contents).map_err(|err| format!("{:?}", err))
+  }
+
+  pub fn remove(key: &str) -> Result<(), String> {
+    Self::local_storage()?.remove_item(key).map_err(|err| format!("{:?}", err))
+  }
+
+  /// Seconds since the unix epoch
+  pub fn now() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+  }
+}